@@ -6,14 +6,23 @@ use std::{env, time::Duration};
 use tiny_http::{Response, Server};
 use url::Url;
 
+use crate::select::Component;
 use crate::Commands;
 
 const REDIRECT_URI: &str = "http://127.0.0.1:8000/callback";
 
 // Scopes for Spotify API
-const SCOPE_EXPORT: &str = "user-library-read playlist-read-private";
-const SCOPE_IMPORT: &str = "user-library-modify playlist-modify-public playlist-modify-private";
-const SCOPE_PURGE: &str = "user-library-read user-library-modify playlist-read-private playlist-modify-public playlist-modify-private";
+const SCOPE_EXPORT: &str = "user-library-read playlist-read-private user-follow-read user-read-playback-position";
+const SCOPE_IMPORT: &str = "user-library-modify playlist-modify-public playlist-modify-private user-follow-modify user-read-private";
+// Only requested when playlists are imported, to restore their covers.
+const SCOPE_COVER_UPLOAD: &str = "ugc-image-upload";
+// Only requested when the listening history is exported.
+const SCOPE_LISTENING_HISTORY: &str = "user-top-read user-read-recently-played";
+// Only requested by exports that read the account's country, product and
+// explicit content settings: the profile, and `--market from_token`.
+const SCOPE_PRIVATE: &str = "user-read-private";
+// Listing saved episodes needs `user-read-playback-position`, as in exports.
+const SCOPE_PURGE: &str = "user-library-read user-library-modify playlist-read-private playlist-modify-public playlist-modify-private user-read-playback-position";
 
 #[derive(Deserialize)]
 struct AccessTokenResponse {
@@ -116,9 +125,25 @@ fn get_authorization_code(command: Commands, client_id: &str, redirect_uri: &Url
     };
 
     let scope = match command {
-        Commands::Export { .. } => SCOPE_EXPORT,
-        Commands::Import { .. } => SCOPE_IMPORT,
-        Commands::Purge { .. } => SCOPE_PURGE,
+        Commands::Export { select, market, .. } => {
+            let mut scope = SCOPE_EXPORT.to_string();
+            if select.includes(Component::ListeningHistory) {
+                scope = format!("{} {}", scope, SCOPE_LISTENING_HISTORY);
+            }
+            if select.includes(Component::Profile) || market.as_deref() == Some("from_token") {
+                scope = format!("{} {}", scope, SCOPE_PRIVATE);
+            }
+            scope
+        }
+        Commands::Status { .. } => SCOPE_EXPORT.to_string(),
+        Commands::Import { select, .. } if select.includes(Component::Playlists) => {
+            format!("{} {}", SCOPE_IMPORT, SCOPE_COVER_UPLOAD)
+        }
+        Commands::Import { .. } => SCOPE_IMPORT.to_string(),
+        Commands::Purge { .. } => SCOPE_PURGE.to_string(),
+        Commands::Convert { .. } | Commands::Verify { .. } | Commands::Diff { .. } | Commands::Prune { .. } => {
+            return Err(anyhow::anyhow!("This command works offline and needs no authorization."))
+        }
     };
 
     let auth_url = Url::parse_with_params(
@@ -128,7 +153,7 @@ fn get_authorization_code(command: Commands, client_id: &str, redirect_uri: &Url
             ("response_type", "code"),
             ("redirect_uri", redirect_uri.as_str()),
             ("state", &state),
-            ("scope", scope),
+            ("scope", &scope),
         ],
     )?;
 
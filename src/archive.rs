@@ -0,0 +1,220 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::crypto;
+use crate::manifest::{Manifest, MANIFEST_FILE};
+
+/// Single-file archive formats a dump can be bundled into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    TarZst,
+    Zip,
+}
+
+impl ArchiveKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveKind::TarZst)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+
+    /// Recognizes an archive from its first bytes, for files whose name says nothing (decrypted data).
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(ArchiveKind::TarZst)
+        } else if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Outcome of checking the files of a dump against the checksums in its manifest.
+#[derive(Debug, Default)]
+pub struct Verification {
+    pub verified: usize,
+    /// Listed in the manifest but absent from the archive.
+    pub missing: Vec<String>,
+    /// Present, but with a different checksum.
+    pub mismatched: Vec<String>,
+    /// Present, but not listed in the manifest.
+    pub unlisted: Vec<String>,
+}
+
+impl Verification {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty() && self.unlisted.is_empty()
+    }
+}
+
+/// Records the SHA-256 of every file of the dump in its manifest, then bundles
+/// the dump into a single archive. The archive type follows its extension
+/// (`.tar.zst` or `.zip`).
+pub fn create(dump_dir: &Path, archive_path: &Path) -> Result<()> {
+    let kind = ArchiveKind::from_path(archive_path).with_context(|| {
+        format!(
+            "Unsupported archive type: {} (use .tar.zst or .zip)",
+            archive_path.display()
+        )
+    })?;
+
+    // The archive may be written inside the dump folder; it must not include itself.
+    let archive_path = std::path::absolute(archive_path)?;
+    let files: Vec<String> = list_files(dump_dir)?
+        .into_iter()
+        .filter(|file| file != MANIFEST_FILE)
+        .filter(|file| std::path::absolute(dump_dir.join(file)).ok().as_ref() != Some(&archive_path))
+        .collect();
+
+    let mut manifest = Manifest::load(dump_dir)?;
+    manifest.checksums.clear();
+    for file in &files {
+        manifest
+            .checksums
+            .insert(file.clone(), sha256_file(&dump_dir.join(file))?);
+    }
+    manifest.save(dump_dir)?;
+
+    let output = File::create(&archive_path)
+        .with_context(|| format!("Failed to create archive: {}", archive_path.display()))?;
+    let entries = std::iter::once(MANIFEST_FILE.to_string()).chain(files);
+
+    match kind {
+        ArchiveKind::TarZst => {
+            let encoder = zstd::Encoder::new(BufWriter::new(output), 0)?;
+            let mut builder = tar::Builder::new(encoder);
+            for file in entries {
+                builder.append_path_with_name(dump_dir.join(&file), &file)?;
+            }
+            builder.into_inner()?.finish()?.flush()?;
+        }
+        ArchiveKind::Zip => {
+            let mut writer = ZipWriter::new(BufWriter::new(output));
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+            for file in entries {
+                writer.start_file(file.as_str(), options)?;
+                io::copy(&mut File::open(dump_dir.join(&file))?, &mut writer)?;
+            }
+            writer.finish()?.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Unpacks an archive into `target_dir` without checking its contents.
+/// Encrypted archives are decrypted first, asking for the passphrase.
+pub fn extract(archive_path: &Path, target_dir: &Path) -> Result<()> {
+    if crypto::is_encrypted(archive_path)? {
+        let data = fs::read(archive_path)
+            .with_context(|| format!("Failed to open archive: {}", archive_path.display()))?;
+        let passphrase = crypto::passphrase(false)?;
+        let decrypted = crypto::decrypt(&data, &passphrase)
+            .with_context(|| format!("Failed to decrypt {}", archive_path.display()))?;
+        let kind = ArchiveKind::sniff(&decrypted)
+            .context("The decrypted data is not a .tar.zst or .zip archive")?;
+        return extract_from(kind, Cursor::new(decrypted), target_dir)
+            .with_context(|| format!("Failed to extract archive: {}", archive_path.display()));
+    }
+
+    let kind = ArchiveKind::from_path(archive_path)
+        .with_context(|| format!("Unsupported archive type: {}", archive_path.display()))?;
+    let input = File::open(archive_path)
+        .with_context(|| format!("Failed to open archive: {}", archive_path.display()))?;
+    extract_from(kind, BufReader::new(input), target_dir)
+        .with_context(|| format!("Failed to extract archive: {}", archive_path.display()))
+}
+
+fn extract_from<R: Read + Seek>(kind: ArchiveKind, reader: R, target_dir: &Path) -> Result<()> {
+    match kind {
+        ArchiveKind::TarZst => tar::Archive::new(zstd::Decoder::new(reader)?).unpack(target_dir)?,
+        ArchiveKind::Zip => ZipArchive::new(reader)?.extract(target_dir)?,
+    }
+    Ok(())
+}
+
+/// Checks the files of an extracted dump against the checksums in its manifest.
+pub fn check_dir(dump_dir: &Path) -> Result<Verification> {
+    let manifest = Manifest::load(dump_dir)?;
+    if manifest.checksums.is_empty() {
+        return Err(anyhow::anyhow!(
+            "The manifest has no checksums; was this dump written with --archive?"
+        ));
+    }
+
+    let mut verification = Verification::default();
+    let files = list_files(dump_dir)?;
+
+    for (file, expected) in &manifest.checksums {
+        if !files.contains(file) {
+            verification.missing.push(file.clone());
+        } else if &sha256_file(&dump_dir.join(file))? != expected {
+            verification.mismatched.push(file.clone());
+        } else {
+            verification.verified += 1;
+        }
+    }
+    verification.unlisted = files
+        .into_iter()
+        .filter(|file| file != MANIFEST_FILE && !manifest.checksums.contains_key(file))
+        .collect();
+
+    Ok(verification)
+}
+
+/// Extracts an archive into a scratch directory and checks it, without
+/// leaving anything behind.
+pub fn verify(archive_path: &Path) -> Result<Verification> {
+    let scratch = tempfile::tempdir()?;
+    extract(archive_path, scratch.path())?;
+    check_dir(scratch.path())
+}
+
+/// Lists every file below `dir` as a `/`-separated relative path, sorted.
+/// The history kept by `export --git` is not part of the dump.
+fn list_files(dir: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+
+    while let Some(relative) = pending.pop() {
+        for entry in fs::read_dir(dir.join(&relative))? {
+            let entry = entry?;
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            let relative = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(relative);
+            } else {
+                let parts: Vec<String> = relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                files.push(parts.join("/"));
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,181 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::dump::{PlaylistDump, Source};
+use crate::format::TrackRecord;
+
+/// Playlist file formats understood by media players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PlaylistFormat {
+    /// Extended M3U in UTF-8, with `#EXTINF` durations.
+    M3u8,
+    /// XML Shareable Playlist Format.
+    Xspf,
+}
+
+impl PlaylistFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u8 => "m3u8",
+            PlaylistFormat::Xspf => "xspf",
+        }
+    }
+}
+
+/// Converts every playlist of a dump into media player playlists, written to
+/// one subdirectory per format in `output_dir` (e.g. `dump/m3u8`).
+pub fn convert_dump(
+    source: &Source,
+    output_dir: &Path,
+    formats: &[PlaylistFormat],
+    force: bool,
+) -> Result<()> {
+    let playlists = source.read_playlists()?;
+    write_playlists(&playlists, output_dir, formats, force)
+}
+
+pub fn write_playlists(
+    playlists: &[PlaylistDump],
+    output_dir: &Path,
+    formats: &[PlaylistFormat],
+    force: bool,
+) -> Result<()> {
+    for &format in formats {
+        let format_dir = output_dir.join(format.extension());
+        if !force {
+            println!(
+                "Dry run: would have written {} {} playlists to {}",
+                playlists.len(),
+                format.extension(),
+                format_dir.display()
+            );
+            continue;
+        }
+
+        fs::create_dir_all(&format_dir)
+            .with_context(|| format!("Failed to create directory: {}", format_dir.display()))?;
+        for playlist in playlists {
+            let contents = match format {
+                PlaylistFormat::M3u8 => to_m3u8(playlist),
+                PlaylistFormat::Xspf => to_xspf(playlist),
+            };
+            let output_file = format_dir.join(format!("{}.{}", file_stem(playlist), format.extension()));
+            fs::write(&output_file, contents)
+                .with_context(|| format!("Failed to write playlist: {}", output_file.display()))?;
+        }
+        println!(
+            "{} playlists have been written to {}",
+            playlists.len(),
+            format_dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Reuses the collision-free stem of the playlist's dump file when there is one.
+fn file_stem(playlist: &PlaylistDump) -> String {
+    playlist
+        .entry
+        .as_ref()
+        .and_then(|entry| Path::new(&entry.file).file_stem())
+        .and_then(|stem| stem.to_str())
+        .filter(|stem| !stem.is_empty())
+        .map(String::from)
+        .or_else(|| playlist.entry.as_ref().map(|entry| entry.id.clone()))
+        .unwrap_or_else(|| playlist.name.clone())
+}
+
+pub fn to_m3u8(playlist: &PlaylistDump) -> String {
+    let mut contents = String::from("#EXTM3U\n");
+    contents.push_str(&format!("#PLAYLIST:{}\n", single_line(&playlist.name)));
+
+    for track in &playlist.tracks {
+        // -1 is the conventional "unknown length", e.g. for legacy CSV dumps.
+        let duration = track
+            .duration_ms
+            .map(|ms| (ms / 1000) as i64)
+            .unwrap_or(-1);
+        contents.push_str(&format!(
+            "#EXTINF:{},{} - {}\n",
+            duration,
+            single_line(&artist_names(track)),
+            single_line(&track.name)
+        ));
+        if !track.album.name.is_empty() {
+            contents.push_str(&format!("#EXTALB:{}\n", single_line(&track.album.name)));
+        }
+        contents.push_str(&track.uri());
+        contents.push('\n');
+    }
+
+    contents
+}
+
+pub fn to_xspf(playlist: &PlaylistDump) -> String {
+    let mut contents = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    contents.push_str("<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n");
+    contents.push_str(&format!("  <title>{}</title>\n", escape_xml(&playlist.name)));
+    if let Some(entry) = &playlist.entry {
+        if let Some(description) = &entry.description {
+            contents.push_str(&format!("  <annotation>{}</annotation>\n", escape_xml(description)));
+        }
+        contents.push_str(&format!(
+            "  <identifier>spotify:playlist:{}</identifier>\n",
+            escape_xml(&entry.id)
+        ));
+    }
+
+    contents.push_str("  <trackList>\n");
+    for track in &playlist.tracks {
+        let uri = escape_xml(&track.uri());
+        contents.push_str("    <track>\n");
+        contents.push_str(&format!("      <location>{}</location>\n", uri));
+        contents.push_str(&format!("      <identifier>{}</identifier>\n", uri));
+        if let Some(isrc) = &track.isrc {
+            contents.push_str(&format!("      <identifier>isrc:{}</identifier>\n", escape_xml(isrc)));
+        }
+        contents.push_str(&format!("      <title>{}</title>\n", escape_xml(&track.name)));
+        contents.push_str(&format!("      <creator>{}</creator>\n", escape_xml(&artist_names(track))));
+        if !track.album.name.is_empty() {
+            contents.push_str(&format!("      <album>{}</album>\n", escape_xml(&track.album.name)));
+        }
+        if let Some(duration_ms) = track.duration_ms {
+            contents.push_str(&format!("      <duration>{}</duration>\n", duration_ms));
+        }
+        contents.push_str("    </track>\n");
+    }
+    contents.push_str("  </trackList>\n");
+    contents.push_str("</playlist>\n");
+
+    contents
+}
+
+fn artist_names(track: &TrackRecord) -> String {
+    track
+        .artists
+        .iter()
+        .map(|artist| artist.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// M3U is line based, so names must not contain line breaks.
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use std::fs;
+use std::path::Path;
+
+use crate::types::Image;
+
+/// Folder of the dump holding playlist covers, one JPEG per playlist file.
+pub const COVERS_DIR: &str = "covers";
+
+/// Spotify rejects cover uploads whose base64 payload is larger than this.
+pub const MAX_UPLOAD_SIZE: usize = 256 * 1024;
+
+/// The cover worth keeping from a playlist's images: the first, which is the
+/// largest. Covers Spotify generates from the first tracks (served from
+/// `mosaic.scdn.co`) are skipped, since uploading them would freeze a mosaic
+/// that otherwise follows the playlist's contents.
+pub fn custom_cover(images: &[Image]) -> Option<&Image> {
+    images
+        .first()
+        .filter(|image| !image.url.contains("mosaic.scdn.co"))
+}
+
+/// Downloads a cover from Spotify's image CDN, which needs no authorization.
+pub async fn download(url: &str, path: &Path) -> Result<()> {
+    let response = reqwest::get(url).await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to download cover {}: {}", url, response.status()));
+    }
+    let bytes = response.bytes().await?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, &bytes).with_context(|| format!("Failed to write cover: {}", path.display()))
+}
+
+/// Turns a cover into the base64 JPEG payload Spotify accepts. Covers that
+/// are too large are recompressed, then scaled down, until they fit.
+pub fn encode_for_upload(bytes: &[u8]) -> Result<String> {
+    let is_jpeg = bytes.starts_with(&[0xFF, 0xD8]);
+    let encoded = general_purpose::STANDARD.encode(bytes);
+    if is_jpeg && encoded.len() <= MAX_UPLOAD_SIZE {
+        return Ok(encoded);
+    }
+
+    let mut image = DynamicImage::ImageRgb8(
+        image::load_from_memory(bytes)
+            .context("Failed to decode cover image")?
+            .to_rgb8(),
+    );
+    loop {
+        for quality in [90, 80, 70, 60] {
+            let mut jpeg = Vec::new();
+            image.write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, quality))?;
+            let encoded = general_purpose::STANDARD.encode(&jpeg);
+            if encoded.len() <= MAX_UPLOAD_SIZE {
+                return Ok(encoded);
+            }
+        }
+        if image.width() <= 64 || image.height() <= 64 {
+            return Err(anyhow::anyhow!("Cover image cannot be made small enough to upload"));
+        }
+        image = image.resize(image.width() * 3 / 4, image.height() * 3 / 4, FilterType::Lanczos3);
+    }
+}
+
+/// Replaces the cover of a playlist. Needs the `ugc-image-upload` scope.
+pub async fn upload(
+    client: &reqwest::Client,
+    access_token: &str,
+    playlist_id: &str,
+    encoded: String,
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("image/jpeg"));
+
+    let url = format!("https://api.spotify.com/v1/playlists/{}/images", playlist_id);
+
+    let response = client.put(&url).headers(headers).body(encoded).send().await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Spotify answered {}", response.status()));
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+use crate::archive;
+
+/// Identifies an encrypted spoti-dump file; the last byte is the format version.
+const MAGIC: &[u8; 8] = b"SPDUMPE\x01";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+// Argon2id cost used for new files: 64 MiB of memory, 3 passes, 1 lane.
+const MEMORY_COST_KIB: u32 = 64 * 1024;
+const TIME_COST: u32 = 3;
+const PARALLELISM: u32 = 1;
+// Refuse to derive keys with absurd costs read from a (possibly forged) header.
+const MAX_MEMORY_COST_KIB: u32 = 1024 * 1024;
+const MAX_TIME_COST: u32 = 64;
+const MAX_PARALLELISM: u32 = 64;
+
+pub const PASSPHRASE_ENV: &str = "SPOTI_DUMP_PASSPHRASE";
+
+/// Encrypts `plaintext` with XChaCha20-Poly1305 under a key derived from the
+/// passphrase with Argon2id. The header (KDF parameters, salt and nonce) is
+/// authenticated too, so any modification is detected on decryption.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&MEMORY_COST_KIB.to_le_bytes());
+    header.extend_from_slice(&TIME_COST.to_le_bytes());
+    header.extend_from_slice(&PARALLELISM.to_le_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let key = derive_key(passphrase, &salt, MEMORY_COST_KIB, TIME_COST, PARALLELISM)?;
+    let ciphertext = XChaCha20Poly1305::new(&key.into())
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt data"))?;
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    if !is_encrypted_data(data) || data.len() < HEADER_LEN {
+        return Err(anyhow::anyhow!("Not an encrypted spoti-dump file, or it is truncated"));
+    }
+
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes(header[offset..offset + 4].try_into().expect("slice is 4 bytes"))
+    };
+    let memory_cost = read_u32(MAGIC.len());
+    let time_cost = read_u32(MAGIC.len() + 4);
+    let parallelism = read_u32(MAGIC.len() + 8);
+    let salt = &header[MAGIC.len() + 12..MAGIC.len() + 12 + SALT_LEN];
+    let nonce = &header[MAGIC.len() + 12 + SALT_LEN..];
+
+    if memory_cost > MAX_MEMORY_COST_KIB || time_cost > MAX_TIME_COST || parallelism > MAX_PARALLELISM {
+        return Err(anyhow::anyhow!("Encrypted file header is corrupted"));
+    }
+
+    let key = derive_key(passphrase, salt, memory_cost, time_cost, parallelism)?;
+    XChaCha20Poly1305::new(&key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase, or the file has been tampered with"))
+}
+
+/// Archives a dump staged in a temporary folder, encrypts the archive and
+/// removes the staged files, so an encrypted export leaves nothing readable
+/// behind. Returns the path of the encrypted archive.
+pub fn seal_dump(staging: TempDir, archive_path: &Path, passphrase: &str) -> Result<PathBuf> {
+    archive::create(staging.path(), archive_path)?;
+    let encrypted_path = encrypt_file(archive_path, passphrase)?;
+    staging.close().context("Failed to remove the staged dump")?;
+    Ok(encrypted_path)
+}
+
+/// Replaces a file by its encrypted copy with `.enc` appended to the name.
+pub fn encrypt_file(path: &Path, passphrase: &str) -> Result<PathBuf> {
+    let plaintext =
+        fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let mut encrypted_path = path.as_os_str().to_owned();
+    encrypted_path.push(".enc");
+    let encrypted_path = PathBuf::from(encrypted_path);
+
+    fs::write(&encrypted_path, encrypt(&plaintext, passphrase)?)
+        .with_context(|| format!("Failed to write file: {}", encrypted_path.display()))?;
+    fs::remove_file(path)
+        .with_context(|| format!("Failed to remove unencrypted file: {}", path.display()))?;
+    Ok(encrypted_path)
+}
+
+pub fn is_encrypted_data(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Checks whether a file starts with the encrypted-file header.
+pub fn is_encrypted(path: &Path) -> Result<bool> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+    let mut magic = [0u8; MAGIC.len()];
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(is_encrypted_data(&magic)),
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Reads the passphrase from `SPOTI_DUMP_PASSPHRASE`, or prompts for it on
+/// the terminal. New passphrases are asked twice to catch typos.
+pub fn passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+
+    let passphrase = rpassword::prompt_password("Backup passphrase: ")
+        .context("Failed to read the passphrase")?;
+    if passphrase.is_empty() {
+        return Err(anyhow::anyhow!("The passphrase must not be empty."));
+    }
+    if confirm {
+        let repeated = rpassword::prompt_password("Repeat the passphrase: ")
+            .context("Failed to read the passphrase")?;
+        if repeated != passphrase {
+            return Err(anyhow::anyhow!("The passphrases do not match."));
+        }
+    }
+    Ok(passphrase)
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    memory_cost: u32,
+    time_cost: u32,
+    parallelism: u32,
+) -> Result<[u8; 32]> {
+    let params = Params::new(memory_cost, time_cost, parallelism, Some(32))
+        .map_err(|err| anyhow::anyhow!("Invalid key derivation parameters: {}", err))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow::anyhow!("Failed to derive the encryption key: {}", err))?;
+    Ok(key)
+}
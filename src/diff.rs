@@ -0,0 +1,303 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::dump::PlaylistDump;
+use crate::format::TrackRecord;
+
+/// Differences between two dumps, from the older one to the newer one.
+#[derive(Debug, Default, Serialize)]
+pub struct DumpDiff {
+    pub saved_tracks: TrackListDiff,
+    pub playlists_added: Vec<PlaylistSummary>,
+    pub playlists_removed: Vec<PlaylistSummary>,
+    /// Playlists present in both dumps that were renamed or whose items changed.
+    pub playlists_changed: Vec<PlaylistDiff>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TrackListDiff {
+    pub added: Vec<TrackChange>,
+    pub removed: Vec<TrackChange>,
+    /// Tracks in both lists whose position changed relative to the others.
+    pub moved: Vec<TrackChange>,
+}
+
+/// A track of a list. `position` is 1-based, in the newer list for added and
+/// moved tracks and in the older one for removed tracks.
+#[derive(Debug, Serialize)]
+pub struct TrackChange {
+    pub id: String,
+    pub name: String,
+    pub artists: Vec<String>,
+    pub position: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlaylistSummary {
+    /// `None` for dumps written before the manifest existed.
+    pub id: Option<String>,
+    pub name: String,
+    pub tracks: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlaylistDiff {
+    pub id: Option<String>,
+    pub name: String,
+    pub renamed_from: Option<String>,
+    #[serde(flatten)]
+    pub tracks: TrackListDiff,
+}
+
+impl TrackListDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+impl DumpDiff {
+    pub fn is_empty(&self) -> bool {
+        self.saved_tracks.is_empty()
+            && self.playlists_added.is_empty()
+            && self.playlists_removed.is_empty()
+            && self.playlists_changed.is_empty()
+    }
+
+    /// Prints the differences for people; use serde for machine-readable output.
+    pub fn print(&self) {
+        if self.is_empty() {
+            println!("No differences.");
+            return;
+        }
+
+        if !self.saved_tracks.is_empty() {
+            println!("Saved tracks: {}", self.saved_tracks.counts());
+            self.saved_tracks.print_changes();
+        }
+        for playlist in &self.playlists_added {
+            println!("Playlist added: '{}' ({} tracks)", playlist.name, playlist.tracks);
+        }
+        for playlist in &self.playlists_removed {
+            println!("Playlist removed: '{}' ({} tracks)", playlist.name, playlist.tracks);
+        }
+        for playlist in &self.playlists_changed {
+            match &playlist.renamed_from {
+                Some(old_name) => print!("Playlist '{}' (renamed from '{}')", playlist.name, old_name),
+                None => print!("Playlist '{}'", playlist.name),
+            }
+            if playlist.tracks.is_empty() {
+                println!();
+            } else {
+                println!(": {}", playlist.tracks.counts());
+                playlist.tracks.print_changes();
+            }
+        }
+    }
+}
+
+impl TrackListDiff {
+    fn counts(&self) -> String {
+        let mut counts = vec![
+            format!("{} added", self.added.len()),
+            format!("{} removed", self.removed.len()),
+        ];
+        if !self.moved.is_empty() {
+            counts.push(format!("{} moved", self.moved.len()));
+        }
+        counts.join(", ")
+    }
+
+    fn print_changes(&self) {
+        for track in &self.added {
+            println!("  + {}", track.describe());
+        }
+        for track in &self.removed {
+            println!("  - {}", track.describe());
+        }
+        for track in &self.moved {
+            println!("  ~ {} (now at position {})", track.describe(), track.position);
+        }
+    }
+}
+
+impl TrackChange {
+    /// `Artists - Title`, as shown to people.
+    pub fn describe(&self) -> String {
+        if self.artists.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} - {}", self.artists.join(", "), self.name)
+        }
+    }
+}
+
+/// Compares two dumps. Playlists are matched by ID, then the rest by name
+/// (playlists recreated by `import` get new IDs, and dumps without a manifest
+/// have none). Tracks are matched by ID, so renames and reorders are told
+/// apart from additions and removals.
+pub fn diff(
+    old_saved_tracks: &[TrackRecord],
+    old_playlists: &[PlaylistDump],
+    new_saved_tracks: &[TrackRecord],
+    new_playlists: &[PlaylistDump],
+) -> DumpDiff {
+    let mut result = DumpDiff {
+        saved_tracks: diff_tracks(old_saved_tracks, new_saved_tracks),
+        ..Default::default()
+    };
+
+    let matches = match_playlists(old_playlists, new_playlists);
+    for (playlist, matched) in new_playlists.iter().zip(&matches) {
+        let Some(old) = matched.map(|index| &old_playlists[index]) else {
+            result.playlists_added.push(summary(playlist));
+            continue;
+        };
+
+        let tracks = diff_tracks(&old.tracks, &playlist.tracks);
+        let renamed_from = (old.name != playlist.name).then(|| old.name.clone());
+        if renamed_from.is_some() || !tracks.is_empty() {
+            result.playlists_changed.push(PlaylistDiff {
+                id: playlist_id(playlist),
+                name: playlist.name.clone(),
+                renamed_from,
+                tracks,
+            });
+        }
+    }
+
+    for (index, playlist) in old_playlists.iter().enumerate() {
+        if !matches.contains(&Some(index)) {
+            result.playlists_removed.push(summary(playlist));
+        }
+    }
+
+    result
+}
+
+/// For each new playlist, the index of the old playlist it corresponds to.
+fn match_playlists(old: &[PlaylistDump], new: &[PlaylistDump]) -> Vec<Option<usize>> {
+    let old_by_id: HashMap<&str, usize> = old
+        .iter()
+        .enumerate()
+        .filter_map(|(index, playlist)| Some((playlist.entry.as_ref()?.id.as_str(), index)))
+        .collect();
+
+    let mut used = vec![false; old.len()];
+    let mut matches: Vec<Option<usize>> = new
+        .iter()
+        .map(|playlist| {
+            let index = *old_by_id.get(playlist.entry.as_ref()?.id.as_str())?;
+            used[index] = true;
+            Some(index)
+        })
+        .collect();
+
+    for (playlist, matched) in new.iter().zip(matches.iter_mut()) {
+        if matched.is_some() {
+            continue;
+        }
+        *matched = (0..old.len()).find(|&index| !used[index] && old[index].name == playlist.name);
+        if let Some(index) = *matched {
+            used[index] = true;
+        }
+    }
+
+    matches
+}
+
+/// Compares two track lists. A track that occurs several times is matched
+/// occurrence by occurrence.
+pub fn diff_tracks(old: &[TrackRecord], new: &[TrackRecord]) -> TrackListDiff {
+    let old_keys = occurrence_keys(old);
+    let new_keys = occurrence_keys(new);
+    let old_positions: HashMap<&(String, usize), usize> =
+        old_keys.iter().enumerate().map(|(index, key)| (key, index)).collect();
+    let new_positions: HashMap<&(String, usize), usize> =
+        new_keys.iter().enumerate().map(|(index, key)| (key, index)).collect();
+
+    let mut result = TrackListDiff::default();
+    for (index, key) in old_keys.iter().enumerate() {
+        if !new_positions.contains_key(key) {
+            result.removed.push(track_change(&old[index], index));
+        }
+    }
+
+    // Tracks kept in both lists, in their new order, with their old positions.
+    let mut kept = Vec::new();
+    for (index, key) in new_keys.iter().enumerate() {
+        match old_positions.get(key) {
+            Some(&old_index) => kept.push((index, old_index)),
+            None => result.added.push(track_change(&new[index], index)),
+        }
+    }
+
+    // The longest run that kept its relative order stayed in place; the rest moved.
+    let old_indices: Vec<usize> = kept.iter().map(|&(_, old_index)| old_index).collect();
+    let in_place = longest_increasing_subsequence(&old_indices);
+    for (&(index, _), in_place) in kept.iter().zip(in_place) {
+        if !in_place {
+            result.moved.push(track_change(&new[index], index));
+        }
+    }
+
+    result
+}
+
+fn occurrence_keys(tracks: &[TrackRecord]) -> Vec<(String, usize)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    tracks
+        .iter()
+        .map(|track| {
+            // Local files have no ID; their URI names the file instead.
+            let key = if track.is_local { track.uri() } else { track.id.clone() };
+            let occurrence = seen.entry(key.clone()).or_default();
+            *occurrence += 1;
+            (key, *occurrence)
+        })
+        .collect()
+}
+
+/// Marks the elements of one longest strictly increasing subsequence.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<bool> {
+    // tails[k] is the index of the smallest possible last value of a run of length k + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+    for (index, &value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&tail| values[tail] < value);
+        previous[index] = length.checked_sub(1).map(|before| tails[before]);
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+
+    let mut in_sequence = vec![false; values.len()];
+    let mut current = tails.last().copied();
+    while let Some(index) = current {
+        in_sequence[index] = true;
+        current = previous[index];
+    }
+    in_sequence
+}
+
+fn track_change(track: &TrackRecord, index: usize) -> TrackChange {
+    TrackChange {
+        id: track.id.clone(),
+        name: track.name.clone(),
+        artists: track.artists.iter().map(|artist| artist.name.clone()).collect(),
+        position: index + 1,
+    }
+}
+
+fn playlist_id(playlist: &PlaylistDump) -> Option<String> {
+    playlist.entry.as_ref().map(|entry| entry.id.clone())
+}
+
+fn summary(playlist: &PlaylistDump) -> PlaylistSummary {
+    PlaylistSummary {
+        id: playlist_id(playlist),
+        name: playlist.name.clone(),
+        tracks: playlist.tracks.len(),
+    }
+}
@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+use crate::archive::{self, ArchiveKind};
+use crate::crypto;
+use crate::format::{
+    self, ArtistProfileRecord, Format, Record, SavedAlbumRecord, SavedAudiobookRecord, SavedEpisodeRecord,
+    SavedShowRecord, TrackRecord,
+};
+use crate::snapshot;
+use crate::manifest::{Manifest, PlaylistEntry, Profile, MANIFEST_FILE};
+use crate::sqlite::Library;
+
+/// Stems of the files holding library components other than playlists (e.g.
+/// `saved_tracks.csv`). Playlist files never use these names.
+pub const COMPONENT_FILES: &[&str] = &[
+    "saved_tracks",
+    "saved_albums",
+    "followed_artists",
+    "saved_shows",
+    "saved_episodes",
+    "saved_audiobooks",
+    "enrichment",
+];
+
+/// A dump folder on disk. Archives are extracted into a temporary directory
+/// that is removed when this value is dropped.
+pub struct DumpDir {
+    path: PathBuf,
+    _extracted: Option<TempDir>,
+}
+
+impl DumpDir {
+    /// Opens a dump folder (its latest snapshot, if it holds snapshots), or
+    /// extracts an archive (decrypting it if needed) after checking it against
+    /// the checksums in its manifest.
+    pub fn open(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return Ok(DumpDir {
+                path: snapshot::resolve(path)?,
+                _extracted: None,
+            });
+        }
+
+        if ArchiveKind::from_path(path).is_none() && !crypto::is_encrypted(path)? {
+            return Err(anyhow::anyhow!(
+                "{} is neither a dump folder nor a .tar.zst/.zip archive",
+                path.display()
+            ));
+        }
+
+        let extracted = tempfile::tempdir().context("Failed to create a temporary directory")?;
+        archive::extract(path, extracted.path())?;
+        let verification = archive::check_dir(extracted.path())?;
+        if !verification.is_ok() {
+            return Err(anyhow::anyhow!(
+                "Archive {} failed verification; run `spoti-dump verify` for details",
+                path.display()
+            ));
+        }
+
+        Ok(DumpDir {
+            path: extracted.path().to_path_buf(),
+            _extracted: Some(extracted),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_archive(&self) -> bool {
+        self._extracted.is_some()
+    }
+}
+
+/// A playlist as read from (or written to) a dump.
+#[derive(Debug, Clone)]
+pub struct PlaylistDump {
+    pub name: String,
+    /// Metadata from the manifest; `None` for dumps that predate it.
+    pub entry: Option<PlaylistEntry>,
+    pub tracks: Vec<TrackRecord>,
+}
+
+/// Where a library backup is read from.
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// A dump directory as written by `export`. Without an explicit format,
+    /// the one recorded in the manifest is used.
+    Dir {
+        path: PathBuf,
+        format: Option<Format>,
+    },
+    /// An SQLite library written by `export --sqlite`.
+    Sqlite(PathBuf),
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Source::Dir {
+            path: PathBuf::from("dump"),
+            format: None,
+        }
+    }
+}
+
+impl Source {
+    /// Dumps exported without saved tracks (e.g. `--only playlists`) have none.
+    pub fn read_saved_tracks(&self) -> Result<Vec<TrackRecord>> {
+        match self {
+            Source::Dir { .. } => self.read_component("saved_tracks"),
+            Source::Sqlite(path) => Library::open_read_only(path)?.read_saved_tracks(),
+        }
+    }
+
+    /// Dumps written before albums were exported, and SQLite libraries, have none.
+    pub fn read_saved_albums(&self) -> Result<Vec<SavedAlbumRecord>> {
+        self.read_component("saved_albums")
+    }
+
+    pub fn read_followed_artists(&self) -> Result<Vec<ArtistProfileRecord>> {
+        self.read_component("followed_artists")
+    }
+
+    pub fn read_saved_shows(&self) -> Result<Vec<SavedShowRecord>> {
+        self.read_component("saved_shows")
+    }
+
+    pub fn read_saved_episodes(&self) -> Result<Vec<SavedEpisodeRecord>> {
+        self.read_component("saved_episodes")
+    }
+
+    pub fn read_saved_audiobooks(&self) -> Result<Vec<SavedAudiobookRecord>> {
+        self.read_component("saved_audiobooks")
+    }
+
+    /// Only dump folders exported with the `profile` component have one.
+    pub fn read_profile(&self) -> Result<Option<Profile>> {
+        match self {
+            Source::Dir { path, .. } => Ok(Manifest::load(path)?.profile),
+            Source::Sqlite(_) => Ok(None),
+        }
+    }
+
+    /// The markets given to `export --market` for the components of the
+    /// dump, each listed once. SQLite libraries do not record them.
+    pub fn read_markets(&self) -> Result<Vec<String>> {
+        match self {
+            Source::Dir { path, .. } => {
+                let mut markets: Vec<String> = Manifest::load(path)?.markets.into_values().collect();
+                markets.sort();
+                markets.dedup();
+                Ok(markets)
+            }
+            Source::Sqlite(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// The cover downloaded for a playlist, if the dump has it.
+    pub fn cover_file(&self, entry: &PlaylistEntry) -> Option<PathBuf> {
+        match self {
+            Source::Dir { path, .. } => {
+                let file = path.join(entry.cover.as_ref()?);
+                file.is_file().then_some(file)
+            }
+            Source::Sqlite(_) => None,
+        }
+    }
+
+    fn read_component<R: Record>(&self, stem: &str) -> Result<Vec<R>> {
+        match self {
+            Source::Dir { path, format } => {
+                let manifest = Manifest::load(path)?;
+                let format = format.unwrap_or_else(|| manifest.component_format(stem));
+                let file = path.join(format!("{}.{}", stem, format.extension()));
+                if file.is_file() {
+                    format::read_records(&file)
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+            Source::Sqlite(_) => Ok(Vec::new()),
+        }
+    }
+
+    pub fn read_playlists(&self) -> Result<Vec<PlaylistDump>> {
+        match self {
+            Source::Dir { path, format } => {
+                let manifest = Manifest::load(path)?;
+                let format = format.unwrap_or(manifest.format);
+                let mut playlists = Vec::new();
+
+                if !path.join(MANIFEST_FILE).is_file() {
+                    // Dumps written before the manifest existed: the file stem is the only name we have.
+                    for entry in fs::read_dir(path)? {
+                        let file = entry?.path();
+                        if !file.is_file() || Format::from_path(&file) != Some(format) {
+                            continue;
+                        }
+                        let name = file.file_stem().unwrap().to_str().unwrap();
+                        if !COMPONENT_FILES.contains(&name) {
+                            playlists.push(PlaylistDump {
+                                name: name.to_string(),
+                                entry: None,
+                                tracks: format::read_tracks(&file)?,
+                            });
+                        }
+                    }
+                } else {
+                    for entry in manifest.playlists {
+                        playlists.push(PlaylistDump {
+                            name: entry.name.clone(),
+                            tracks: format::read_tracks(&path.join(&entry.file))?,
+                            entry: Some(entry),
+                        });
+                    }
+                }
+
+                Ok(playlists)
+            }
+            Source::Sqlite(path) => Library::open_read_only(path)?.read_playlists(),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::StatusCode;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::format::{self, ArtistRecord, EnrichmentRecord, Format, TrackRecord};
+use crate::types::{FullArtist, Track};
+use crate::utils;
+
+// Largest batches the endpoints accept.
+const TRACKS_PER_REQUEST: usize = 50;
+const ARTISTS_PER_REQUEST: usize = 50;
+const AUDIO_FEATURES_PER_REQUEST: usize = 100;
+
+#[derive(Debug, serde::Deserialize)]
+struct Tracks {
+    tracks: Vec<Option<Track>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Artists {
+    artists: Vec<Option<FullArtist>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct AudioFeaturesPage {
+    audio_features: Vec<Option<AudioFeatures>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AudioFeatures {
+    pub id: String,
+    pub tempo: Option<f64>,
+    /// -1 when no key was detected.
+    pub key: Option<i32>,
+    pub mode: Option<u32>,
+    pub energy: Option<f64>,
+    pub danceability: Option<f64>,
+    pub valence: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct ArtistDetails {
+    pub genres: Vec<String>,
+    pub popularity: Option<u32>,
+}
+
+/// Fetches artist details and audio features for the exported tracks, in
+/// batches, and writes them to `enrichment.<ext>` keyed by track ID.
+pub async fn export_enrichment(
+    access_token: &str,
+    dump_dir: &Path,
+    tracks: &[&TrackRecord],
+    format: Format,
+    force: bool,
+) -> Result<Vec<EnrichmentRecord>> {
+    if !force {
+        println!("Dry run: would have fetched artist details and audio features for the exported tracks.");
+        return Ok(Vec::new());
+    }
+
+    // Tracks in several playlists are only looked up once; local files have no ID.
+    let mut seen = HashSet::new();
+    let tracks: Vec<&TrackRecord> = tracks
+        .iter()
+        .copied()
+        .filter(|track| !track.is_local && !track.id.is_empty())
+        .filter(|track| seen.insert(track.id.as_str()))
+        .collect();
+
+    let missing: Vec<&str> = tracks
+        .iter()
+        .filter(|track| lacks_artist_ids(track))
+        .map(|track| track.id.as_str())
+        .collect();
+    let artists_by_track = fetch_track_artists(access_token, &missing).await?;
+    let tracks = with_artist_ids(tracks, &artists_by_track);
+    let unresolved = tracks.iter().filter(|track| lacks_artist_ids(track)).count();
+    if unresolved > 0 {
+        println!("Found no artist IDs for {} tracks; they get no genres or popularity.", unresolved);
+    }
+
+    let mut seen = HashSet::new();
+    let artist_ids: Vec<&str> = tracks
+        .iter()
+        .flat_map(|track| track.artists.iter())
+        .filter_map(|artist| artist.id.as_deref())
+        .filter(|id| seen.insert(*id))
+        .collect();
+    let artists = fetch_artists(access_token, &artist_ids).await?;
+
+    let track_ids: Vec<&str> = tracks.iter().map(|track| track.id.as_str()).collect();
+    let features = match fetch_audio_features(access_token, &track_ids).await? {
+        Some(features) => features,
+        None => {
+            println!("Spotify does not provide audio features to this app; only artist details are added.");
+            HashMap::new()
+        }
+    };
+
+    let records: Vec<EnrichmentRecord> = tracks
+        .iter()
+        .map(|track| enrichment_record(track, &artists, features.get(&track.id)))
+        .collect();
+
+    if !dump_dir.exists() {
+        fs::create_dir_all(dump_dir).context("Failed to create dump directory")?;
+    }
+    let output_file = dump_dir.join(format!("enrichment.{}", format.extension()));
+    format::write_records(&output_file, format, &records)?;
+
+    println!(
+        "Details of {} tracks and {} artists have been exported to {}",
+        records.len(),
+        artists.len(),
+        output_file.display()
+    );
+    Ok(records)
+}
+
+/// Records read back from CSV, such as playlists reused by `--incremental`
+/// and saved tracks merged from the previous dump, only keep artist names.
+pub fn lacks_artist_ids(track: &TrackRecord) -> bool {
+    track.artists.iter().all(|artist| artist.id.is_none())
+}
+
+/// Replaces the artists of the tracks found in `artists_by_track`, keyed by
+/// track ID, and leaves the others as they are.
+pub fn with_artist_ids<'a>(
+    tracks: Vec<&'a TrackRecord>,
+    artists_by_track: &HashMap<String, Vec<ArtistRecord>>,
+) -> Vec<Cow<'a, TrackRecord>> {
+    tracks
+        .into_iter()
+        .map(|track| match artists_by_track.get(&track.id) {
+            Some(artists) => Cow::Owned(TrackRecord {
+                artists: artists.clone(),
+                ..track.clone()
+            }),
+            None => Cow::Borrowed(track),
+        })
+        .collect()
+}
+
+/// Combines what is known about a track's artists and its audio features.
+pub fn enrichment_record(
+    track: &TrackRecord,
+    artists: &HashMap<String, ArtistDetails>,
+    features: Option<&AudioFeatures>,
+) -> EnrichmentRecord {
+    let details: Vec<&ArtistDetails> = track
+        .artists
+        .iter()
+        .filter_map(|artist| artists.get(artist.id.as_deref()?))
+        .collect();
+
+    let mut genres: Vec<String> = Vec::new();
+    for genre in details.iter().flat_map(|details| details.genres.iter()) {
+        if !genres.contains(genre) {
+            genres.push(genre.clone());
+        }
+    }
+
+    EnrichmentRecord {
+        track_id: track.id.clone(),
+        genres,
+        artist_popularity: details.first().and_then(|details| details.popularity),
+        tempo: features.and_then(|features| features.tempo),
+        key: features
+            .and_then(|features| features.key)
+            .and_then(|key| u32::try_from(key).ok()),
+        mode: features.and_then(|features| features.mode),
+        energy: features.and_then(|features| features.energy),
+        danceability: features.and_then(|features| features.danceability),
+        valence: features.and_then(|features| features.valence),
+    }
+}
+
+async fn fetch_track_artists(access_token: &str, track_ids: &[&str]) -> Result<HashMap<String, Vec<ArtistRecord>>> {
+    let mut artists_by_track = HashMap::new();
+    for chunk in track_ids.chunks(TRACKS_PER_REQUEST) {
+        let url = format!("https://api.spotify.com/v1/tracks?ids={}", chunk.join(","));
+        let page: Tracks = utils::get_json(access_token, &url).await?;
+        for record in page.tracks.into_iter().flatten().filter_map(|track| TrackRecord::from_track(None, track)) {
+            artists_by_track.insert(record.id, record.artists);
+        }
+    }
+    Ok(artists_by_track)
+}
+
+async fn fetch_artists(access_token: &str, artist_ids: &[&str]) -> Result<HashMap<String, ArtistDetails>> {
+    let mut artists = HashMap::new();
+    for chunk in artist_ids.chunks(ARTISTS_PER_REQUEST) {
+        let url = format!("https://api.spotify.com/v1/artists?ids={}", chunk.join(","));
+        let page: Artists = utils::get_json(access_token, &url).await?;
+        for artist in page.artists.into_iter().flatten() {
+            if let Some(id) = artist.id {
+                artists.insert(
+                    id,
+                    ArtistDetails {
+                        genres: artist.genres,
+                        popularity: artist.popularity,
+                    },
+                );
+            }
+        }
+    }
+    Ok(artists)
+}
+
+/// Returns `None` when Spotify refuses audio features altogether, which it
+/// does for apps registered after the endpoint was deprecated.
+async fn fetch_audio_features(
+    access_token: &str,
+    track_ids: &[&str],
+) -> Result<Option<HashMap<String, AudioFeatures>>> {
+    let client = reqwest::Client::new();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+
+    let mut features = HashMap::new();
+    for chunk in track_ids.chunks(AUDIO_FEATURES_PER_REQUEST) {
+        let url = format!("https://api.spotify.com/v1/audio-features?ids={}", chunk.join(","));
+        let response = client.get(&url).headers(headers.clone()).send().await?;
+
+        if response.status() == StatusCode::FORBIDDEN {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Failed to get audio features from Spotify"));
+        }
+
+        let page: AudioFeaturesPage = response.json().await?;
+        for track_features in page.audio_features.into_iter().flatten() {
+            features.insert(track_features.id.clone(), track_features);
+        }
+    }
+    Ok(Some(features))
+}
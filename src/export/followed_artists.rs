@@ -0,0 +1,31 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::format::{ArtistProfileRecord, Format};
+use crate::types::FullArtist;
+use crate::utils;
+
+/// Exports the followed artists into the dump directory and returns what was written.
+pub async fn export_followed_artists(
+    access_token: &str,
+    dump_dir: &Path,
+    format: Format,
+    force: bool,
+) -> Result<Vec<ArtistProfileRecord>> {
+    let artists: Vec<FullArtist> = utils::get_all_cursor_items(
+        access_token,
+        "https://api.spotify.com/v1/me/following?type=artist&limit=50",
+        "artists",
+    )
+    .await?;
+
+    super::export_component(
+        dump_dir,
+        "followed_artists",
+        "followed artists",
+        artists,
+        ArtistProfileRecord::from_artist,
+        format,
+        force,
+    )
+}
@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::Path;
+
+use crate::format::{self, ArtistProfileRecord, Format, PlayRecord, TrackRecord};
+use crate::types::{FullArtist, Track};
+use crate::utils;
+
+/// Folder of the listening history, at the top of the dump folder. Unlike the
+/// rest of the dump it is never overwritten: each export adds dated files.
+pub const HISTORY_DIR: &str = "listening_history";
+
+/// Spotify computes top items over about 4 weeks, 6 months and a year.
+const TIME_RANGES: [&str; 3] = ["short_term", "medium_term", "long_term"];
+
+const RECENTLY_PLAYED: &str = "recently_played";
+
+const SCOPE_HINT: &str = "Listening history needs the user-top-read and user-read-recently-played scopes. \
+    If SPOTIFY_REFRESH_TOKEN was issued without them, remove it from .env and authorize again";
+
+#[derive(Debug, serde::Deserialize)]
+struct RecentlyPlayed {
+    items: Vec<Play>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Play {
+    track: Option<Track>,
+    played_at: String,
+    context: Option<PlayContext>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct PlayContext {
+    uri: Option<String>,
+}
+
+/// Writes the current top tracks and artists of each time range, and the
+/// plays since the last export, into dated files of `history_dir`.
+pub async fn export_listening_history(
+    access_token: &str,
+    history_dir: &Path,
+    format: Format,
+    force: bool,
+) -> Result<()> {
+    let now = Utc::now();
+    let date = now.format("%Y-%m-%d");
+
+    let mut top_tracks = Vec::new();
+    let mut top_artists = Vec::new();
+    for range in TIME_RANGES {
+        let url = format!("https://api.spotify.com/v1/me/top/tracks?time_range={}&limit=50", range);
+        let tracks: Vec<Track> = utils::get_all_items(access_token, &url).await.context(SCOPE_HINT)?;
+        let tracks: Vec<TrackRecord> = tracks
+            .into_iter()
+            .filter_map(|track| TrackRecord::from_track(None, track))
+            .collect();
+        top_tracks.push((range, tracks));
+
+        let url = format!("https://api.spotify.com/v1/me/top/artists?time_range={}&limit=50", range);
+        let artists: Vec<FullArtist> = utils::get_all_items(access_token, &url).await.context(SCOPE_HINT)?;
+        let artists: Vec<ArtistProfileRecord> = artists
+            .into_iter()
+            .filter_map(ArtistProfileRecord::from_artist)
+            .collect();
+        top_artists.push((range, artists));
+    }
+
+    // Spotify only keeps the last 50 plays, so only the ones newer than the
+    // history already on disk are fetched; the files then never overlap.
+    let mut url = String::from("https://api.spotify.com/v1/me/player/recently-played?limit=50");
+    if let Some(last_played_at) = last_played_at(history_dir)? {
+        url.push_str(&format!("&after={}", last_played_at.timestamp_millis()));
+    }
+    let recently_played: RecentlyPlayed = utils::get_json(access_token, &url).await.context(SCOPE_HINT)?;
+    let plays: Vec<PlayRecord> = recently_played
+        .items
+        .into_iter()
+        .filter_map(|play| {
+            let context_uri = play.context.and_then(|context| context.uri);
+            PlayRecord::from_play(play.played_at, play.track?, context_uri)
+        })
+        .collect();
+
+    if !force {
+        println!(
+            "Dry run: would have recorded top tracks and artists for {} time ranges and {} new plays.",
+            TIME_RANGES.len(),
+            plays.len()
+        );
+        return Ok(());
+    }
+
+    fs::create_dir_all(history_dir).context("Failed to create listening history directory")?;
+    let extension = format.extension();
+
+    for (range, tracks) in &top_tracks {
+        let file = history_dir.join(format!("top_tracks_{}_{}.{}", range, date, extension));
+        format::write_records(&file, format, tracks)?;
+    }
+    for (range, artists) in &top_artists {
+        let file = history_dir.join(format!("top_artists_{}_{}.{}", range, date, extension));
+        format::write_records(&file, format, artists)?;
+    }
+    if !plays.is_empty() {
+        let file = history_dir.join(format!("{}_{}.{}", RECENTLY_PLAYED, now.format("%Y-%m-%dT%H-%M-%SZ"), extension));
+        format::write_records(&file, format, &plays)?;
+    }
+
+    println!(
+        "Listening history has been exported to {} ({} new plays).",
+        history_dir.display(),
+        plays.len()
+    );
+    Ok(())
+}
+
+/// The most recent play recorded by earlier exports, in any format.
+pub fn last_played_at(history_dir: &Path) -> Result<Option<DateTime<Utc>>> {
+    if !history_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut last = None;
+    for entry in fs::read_dir(history_dir)? {
+        let file = entry?.path();
+        let is_plays = file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.starts_with(RECENTLY_PLAYED));
+        if !is_plays || Format::from_path(&file).is_none() {
+            continue;
+        }
+        for play in format::read_records::<PlayRecord>(&file)? {
+            if let Ok(played_at) = DateTime::parse_from_rfc3339(&play.played_at) {
+                last = last.max(Some(played_at.with_timezone(&Utc)));
+            }
+        }
+    }
+    Ok(last)
+}
@@ -1,2 +1,62 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::Path;
+
+use crate::format::{self, Format, Record};
+use crate::manifest::Manifest;
+
+pub mod enrichment;
+pub mod followed_artists;
+pub mod listening_history;
 pub mod playlists;
+pub mod profile;
+pub mod saved_albums;
+pub mod saved_audiobooks;
+pub mod saved_episodes;
+pub mod saved_shows;
 pub mod saved_tracks;
+
+/// Writes a library component (`stem`, e.g. `saved_albums`) into the dump
+/// directory and records the export in the manifest. `items` are what
+/// Spotify listed; those `to_record` cannot convert are reported as skipped.
+fn export_component<T, R: Record>(
+    dump_dir: &Path,
+    stem: &str,
+    label: &str,
+    items: Vec<T>,
+    to_record: impl FnMut(T) -> Option<R>,
+    format: Format,
+    force: bool,
+) -> Result<Vec<R>> {
+    if !force {
+        println!("Dry run: would have exported {} {}.", items.len(), label);
+        return Ok(Vec::new());
+    }
+
+    if !dump_dir.exists() {
+        fs::create_dir_all(dump_dir).context("Failed to create dump directory")?;
+    }
+
+    let total = items.len();
+    let records: Vec<R> = items.into_iter().filter_map(to_record).collect();
+    let skipped_count = total - records.len();
+
+    let output_file = dump_dir.join(format!("{}.{}", stem, format.extension()));
+    format::write_records(&output_file, format, &records)?;
+
+    let mut manifest = Manifest::load(dump_dir)?;
+    manifest.exported_at = Some(Utc::now());
+    manifest.formats.insert(stem.to_string(), format);
+    manifest.save(dump_dir)?;
+
+    let mut capitalized = label.to_string();
+    capitalized[..1].make_ascii_uppercase();
+    println!("{} have been exported to {}", capitalized, output_file.display());
+
+    if skipped_count > 0 {
+        println!("Skipped {} {}.", skipped_count, label);
+    }
+
+    Ok(records)
+}
@@ -1,141 +1,506 @@
 use anyhow::{Context, Result};
-use csv::Writer;
-use rand::Rng;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::types::Track;
+use crate::cover::{self, COVERS_DIR};
+use crate::dump::{PlaylistDump, COMPONENT_FILES};
+use crate::format::{self, Format, TrackRecord};
+use crate::manifest::{Contributor, Manifest, PlaylistEntry, MANIFEST_FILE};
+use crate::select::PlaylistFilter;
+use crate::types::{Followers, Image, Owner, Track};
 use crate::utils;
 
 #[derive(Debug, serde::Deserialize)]
 struct Playlist {
     id: String,
     name: String,
+    description: Option<String>,
+    public: Option<bool>,
+    #[serde(default)]
+    collaborative: bool,
+    #[serde(default)]
+    owner: Owner,
+    snapshot_id: Option<String>,
+    #[serde(default)]
+    images: Option<Vec<Image>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct PlaylistDetails {
+    followers: Option<Followers>,
 }
 
 #[derive(Debug, serde::Deserialize)]
 struct PlaylistItem {
     added_at: Option<String>,
+    /// Missing for items added before Spotify recorded it.
+    added_by: Option<AddedBy>,
+    #[serde(default)]
+    is_local: bool,
     track: Option<Track>,
 }
 
-pub async fn export_playlists(access_token: &String, force: bool) -> Result<()> {
-    let playlists: Vec<Playlist> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/playlists").await?;
+#[derive(Debug, serde::Deserialize)]
+struct AddedBy {
+    id: String,
+}
+
+/// Exports the playlists selected by `filter` into the dump directory and
+/// returns what was written, so it can also be stored elsewhere (e.g. an
+/// SQLite library).
+///
+/// With a `previous_dir`, playlists whose `snapshot_id` has not changed since
+/// that dump are copied from it instead of being downloaded again. With a
+/// `market`, each track records whether it is playable there.
+#[allow(clippy::too_many_arguments)]
+pub async fn export_playlists(
+    access_token: &str,
+    user_id: &str,
+    dump_dir: &Path,
+    previous_dir: Option<&Path>,
+    format: Format,
+    market: Option<&str>,
+    filter: &PlaylistFilter,
+    force: bool,
+) -> Result<Vec<PlaylistDump>> {
+    let playlists = fetch_selected_playlists(access_token, filter).await?;
     let mut total_skipped_tracks = 0;
+    let mut reused_count = 0;
+    let mut refreshed_count = 0;
+
+    // Files of another format, or checked for another market, cannot be reused as they are.
+    let previous = match previous_dir {
+        Some(previous_dir) => {
+            let manifest = Manifest::load(previous_dir)?;
+            if manifest.format != format {
+                println!(
+                    "The previous dump is in {} format; refreshing every playlist.",
+                    manifest.format.extension()
+                );
+                None
+            } else if manifest.component_market("playlists") != market {
+                println!("The previous dump was checked for another market; refreshing every playlist.");
+                None
+            } else {
+                Some((previous_dir, manifest))
+            }
+        }
+        None => None,
+    };
 
-    let dump_dir = Path::new("dump");
     if force && !dump_dir.exists() {
-        fs::create_dir(dump_dir).context("Failed to create dump directory")?;
+        fs::create_dir_all(dump_dir).context("Failed to create dump directory")?;
     }
 
-    for playlist in playlists {
-        total_skipped_tracks += export_playlist(access_token, &playlist.id, &playlist.name, dump_dir, force).await?;
+    // Name files after the dump being updated, so each playlist keeps its file
+    // and an in-place export never writes one playlist over another's file
+    // before that file is reused. Playlists left out by the filters keep
+    // their files too.
+    let known = Manifest::load(previous_dir.unwrap_or(dump_dir))?;
+    let mut manifest = Manifest::load(dump_dir)?;
+    let unselected: Vec<PlaylistEntry> = manifest
+        .playlists
+        .iter()
+        .filter(|entry| !filter.is_empty() && !entry_matches(filter, entry))
+        .cloned()
+        .collect();
+    let ids_and_names: Vec<(&str, &str)> = playlists
+        .iter()
+        .map(|playlist| (playlist.id.as_str(), playlist.name.as_str()))
+        .collect();
+    let file_names = playlist_file_names(&ids_and_names, &known.playlists, &unselected, format);
+
+    let mut exported = Vec::new();
+    for (playlist, file_name) in playlists.into_iter().zip(file_names) {
+        let output_file = dump_dir.join(&file_name);
+        let unchanged_file = previous
+            .as_ref()
+            .and_then(|(previous_dir, manifest)| unchanged_file(previous_dir, manifest, &playlist));
+
+        let tracks = if let Some(previous_file) = unchanged_file {
+            reused_count += 1;
+            reuse_playlist(&playlist.name, &previous_file, &output_file, force)?
+        } else {
+            refreshed_count += 1;
+            let (tracks, skipped_tracks) = export_playlist(access_token, &playlist.id, &playlist.name, &output_file, format, market, force).await?;
+            total_skipped_tracks += skipped_tracks;
+            tracks
+        };
+        if force {
+            let mut entry = playlist_entry(access_token, user_id, playlist, file_name).await?;
+            entry.track_count = Some(tracks.len() as u32);
+            entry.cover = export_cover(&entry, dump_dir).await;
+            if entry.collaborative {
+                entry.contributors = contributors(&tracks);
+                print_contributors(&entry);
+            }
+            exported.push(PlaylistDump {
+                name: entry.name.clone(),
+                entry: Some(entry),
+                tracks,
+            });
+        }
     }
 
     if force {
+        manifest.exported_at = Some(Utc::now());
+        manifest.format = format;
+        manifest.set_component_market("playlists", market);
+        let entries = exported
+            .iter()
+            .filter_map(|playlist| playlist.entry.clone())
+            .collect();
+        manifest.playlists = merge_playlist_entries(&manifest.playlists, entries, filter);
+        manifest.save(dump_dir)?;
+
         println!("All playlists have been exported.");
         if total_skipped_tracks > 0 {
             println!("Skipped {} tracks in playlists.", total_skipped_tracks);
         }
     }
-    Ok(())
+    if previous.is_some() {
+        let verb = if force { "Reused" } else { "Dry run: would have reused" };
+        println!(
+            "{} {} unchanged playlists and refreshed {}.",
+            verb, reused_count, refreshed_count
+        );
+    }
+    Ok(exported)
+}
+
+/// Merges the entries of a filtered export into the playlists already in the
+/// manifest: exported playlists replace their entry (matched by ID) or are
+/// appended, playlists the filters leave out are kept, and the rest are
+/// dropped since they no longer exist on Spotify. An unfiltered export
+/// replaces the whole list.
+pub fn merge_playlist_entries(
+    existing: &[PlaylistEntry],
+    exported: Vec<PlaylistEntry>,
+    filter: &PlaylistFilter,
+) -> Vec<PlaylistEntry> {
+    if filter.is_empty() {
+        return exported;
+    }
+
+    let mut exported: Vec<Option<PlaylistEntry>> = exported.into_iter().map(Some).collect();
+    let mut merged = Vec::new();
+    for entry in existing {
+        let replacement = exported
+            .iter_mut()
+            .find(|exported| exported.as_ref().is_some_and(|exported| exported.id == entry.id));
+        match replacement {
+            Some(replacement) => merged.extend(replacement.take()),
+            None if !entry_matches(filter, entry) => merged.push(entry.clone()),
+            None => {}
+        }
+    }
+    merged.extend(exported.into_iter().flatten());
+    merged
+}
+
+fn entry_matches(filter: &PlaylistFilter, entry: &PlaylistEntry) -> bool {
+    let id = Some(entry.id.as_str()).filter(|id| !id.is_empty());
+    let owner_id = Some(entry.owner.id.as_str()).filter(|id| !id.is_empty());
+    filter.matches(id, &entry.name, owner_id)
+}
+
+/// Counts the tracks each user added, most first. Items without an
+/// `added_by` are not counted.
+pub fn contributors(tracks: &[TrackRecord]) -> Vec<Contributor> {
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for user_id in tracks.iter().filter_map(|track| track.added_by.as_deref()) {
+        *counts.entry(user_id).or_default() += 1;
+    }
+
+    let mut contributors: Vec<Contributor> = counts
+        .into_iter()
+        .map(|(user_id, tracks)| Contributor {
+            user_id: user_id.to_string(),
+            tracks,
+        })
+        .collect();
+    contributors.sort_by(|a, b| b.tracks.cmp(&a.tracks).then_with(|| a.user_id.cmp(&b.user_id)));
+    contributors
+}
+
+fn print_contributors(entry: &PlaylistEntry) {
+    if entry.contributors.is_empty() {
+        return;
+    }
+    let summary: Vec<String> = entry
+        .contributors
+        .iter()
+        .map(|contributor| format!("{} ({})", contributor.user_id, contributor.tracks))
+        .collect();
+    println!("Contributors to '{}': {}", entry.name, summary.join(", "));
+}
+
+/// Downloads the playlist's custom cover into the dump. A failed download
+/// only costs the cover, so it is reported and the export goes on.
+async fn export_cover(entry: &PlaylistEntry, dump_dir: &Path) -> Option<String> {
+    let image = cover::custom_cover(&entry.images)?;
+    let stem = Path::new(&entry.file).file_stem()?.to_str()?;
+    let relative = format!("{}/{}.jpg", COVERS_DIR, stem);
+    match cover::download(&image.url, &dump_dir.join(&relative)).await {
+        Ok(()) => Some(relative),
+        Err(err) => {
+            println!("Could not download the cover of playlist '{}': {}", entry.name, err);
+            None
+        }
+    }
+}
+
+/// Finds the file of the previous dump holding this playlist, if its snapshot
+/// is the same as now.
+fn unchanged_file(previous_dir: &Path, manifest: &Manifest, playlist: &Playlist) -> Option<PathBuf> {
+    let snapshot_id = playlist.snapshot_id.as_ref()?;
+    let entry = manifest
+        .playlists
+        .iter()
+        .find(|entry| entry.id == playlist.id)?;
+    if entry.snapshot_id.as_ref() != Some(snapshot_id) {
+        return None;
+    }
+    let file = previous_dir.join(&entry.file);
+    file.is_file().then_some(file)
+}
+
+fn reuse_playlist(
+    playlist_name: &str,
+    previous_file: &Path,
+    output_file: &Path,
+    force: bool,
+) -> Result<Vec<TrackRecord>> {
+    if !force {
+        println!(
+            "Dry run: would have reused unchanged playlist '{}' from {}",
+            playlist_name,
+            previous_file.display()
+        );
+        return Ok(Vec::new());
+    }
+
+    // Copy rather than rewrite, so nothing is lost from formats that keep less than a record.
+    if previous_file != output_file {
+        fs::copy(previous_file, output_file).with_context(|| {
+            format!("Failed to copy {} to {}", previous_file.display(), output_file.display())
+        })?;
+    }
+    println!(
+        "Playlist '{}' is unchanged; reused {}",
+        playlist_name,
+        previous_file.display()
+    );
+    format::read_tracks(output_file)
+}
+
+/// Fetches the playlists selected by `filter` with their tracks, without
+/// writing anything. The entries carry no file name and no follower count.
+pub async fn fetch_playlists(
+    access_token: &str,
+    user_id: &str,
+    filter: &PlaylistFilter,
+) -> Result<Vec<PlaylistDump>> {
+    let playlists = fetch_selected_playlists(access_token, filter).await?;
+
+    let mut fetched = Vec::new();
+    for playlist in playlists {
+        let (tracks, _) = fetch_playlist_tracks(access_token, &playlist.id, None).await?;
+        let mut entry = entry_from(playlist, user_id, String::new(), None);
+        entry.track_count = Some(tracks.len() as u32);
+        fetched.push(PlaylistDump {
+            name: entry.name.clone(),
+            entry: Some(entry),
+            tracks,
+        });
+    }
+    Ok(fetched)
+}
+
+async fn fetch_selected_playlists(access_token: &str, filter: &PlaylistFilter) -> Result<Vec<Playlist>> {
+    let playlists: Vec<Playlist> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/playlists").await?;
+    let total = playlists.len();
+    let selected: Vec<Playlist> = playlists
+        .into_iter()
+        .filter(|playlist| {
+            filter.matches(Some(&playlist.id), &playlist.name, Some(&playlist.owner.id))
+        })
+        .collect();
+    if selected.len() < total {
+        println!(
+            "Skipping {} playlists that do not match the filters.",
+            total - selected.len()
+        );
+    }
+    Ok(selected)
+}
+
+/// Returns the exportable tracks of a playlist and the number of items
+/// Spotify listed, which includes local files and unavailable tracks.
+async fn fetch_playlist_tracks(
+    access_token: &str,
+    playlist_id: &str,
+    market: Option<&str>,
+) -> Result<(Vec<TrackRecord>, usize)> {
+    let url = utils::with_market(
+        &format!("https://api.spotify.com/v1/playlists/{}/tracks", playlist_id),
+        market,
+    );
+    let tracks: Vec<PlaylistItem> = utils::get_all_items(access_token, &url).await?;
+    let total_tracks = tracks.len();
+    let records = tracks
+        .into_iter()
+        .enumerate()
+        .filter_map(|(position, item)| {
+            let added_by = item.added_by.map(|added_by| added_by.id).filter(|id| !id.is_empty());
+            TrackRecord::from_playlist_item(item.added_at, added_by, position as u32, item.is_local, item.track?)
+        })
+        .collect();
+    Ok((records, total_tracks))
 }
 
 async fn export_playlist(
     access_token: &str,
     playlist_id: &str,
     playlist_name: &str,
-    dump_dir: &Path,
+    output_file: &Path,
+    format: Format,
+    market: Option<&str>,
     force: bool,
-) -> Result<u32> {
-    let url = format!(
-        "https://api.spotify.com/v1/playlists/{}/tracks",
-        playlist_id
-    );
-    let tracks: Vec<PlaylistItem> = utils::get_all_items(access_token, &url).await?;
+) -> Result<(Vec<TrackRecord>, u32)> {
+    let (records, total_tracks) = fetch_playlist_tracks(access_token, playlist_id, market).await?;
 
     if !force {
         println!(
             "Dry run: would have exported playlist '{}' with {} tracks.",
             playlist_name,
-            tracks.len()
+            total_tracks
         );
-        return Ok(0);
-    }
-
-    let sanitized_name = sanitize_filename(playlist_name);
-    let output_file = dump_dir.join(format!("{}.csv", sanitized_name));
-    let mut writer = Writer::from_path(&output_file)
-        .with_context(|| format!("Failed to create CSV file: {:?}", output_file))?;
-
-    writer.write_record(&["Added At", "Track Name", "Artists", "Album", "Id"])?;
-
-    let mut skipped_tracks_count = 0;
-
-    for item in tracks {
-        if let Some(track_data) = item.track {
-            let Track { id, name, artists, album } = track_data;
-
-            if let Some(track_id) = id {
-                let added_at = item
-                    .added_at
-                    .clone()
-                    .unwrap_or_else(|| "Unknown".to_string());
-                let track_name = if name.is_empty() {
-                    "Unknown".to_string()
-                } else {
-                    name
-                };
-                let artist_names: Vec<String> = artists
-                    .into_iter()
-                    .filter_map(|artist| {
-                        let trimmed = artist.name.trim().to_string();
-                        if trimmed.is_empty() {
-                            None
-                        } else {
-                            Some(trimmed)
-                        }
-                    })
-                    .collect();
-                let artists_joined = if artist_names.is_empty() {
-                    "Unknown".to_string()
-                } else {
-                    artist_names.join(", ")
-                };
-                let album_name = if album.name.is_empty() {
-                    "Unknown".to_string()
-                } else {
-                    album.name
-                };
-
-                writer.write_record(&[
-                    added_at.as_str(),
-                    track_name.as_str(),
-                    artists_joined.as_str(),
-                    album_name.as_str(),
-                    track_id.as_str(),
-                ])?;
-            } else {
-                skipped_tracks_count += 1;
-            }
-        } else {
-            skipped_tracks_count += 1;
-        }
+        return Ok((Vec::new(), 0));
     }
 
-    writer.flush()?;
+    let skipped_tracks_count = (total_tracks - records.len()) as u32;
+
+    format::write_tracks(output_file, format, &records)?;
     println!(
         "Playlist '{}' has been exported to {}",
         playlist_name,
         output_file.to_str().unwrap()
     );
 
-    Ok(skipped_tracks_count)
+    Ok((records, skipped_tracks_count))
+}
+
+async fn playlist_entry(
+    access_token: &str,
+    user_id: &str,
+    playlist: Playlist,
+    file_name: String,
+) -> Result<PlaylistEntry> {
+    // The simplified playlist objects from /me/playlists do not carry the follower count.
+    let url = format!(
+        "https://api.spotify.com/v1/playlists/{}?fields=followers.total",
+        playlist.id
+    );
+    let details: PlaylistDetails = utils::get_json(access_token, &url).await?;
+
+    Ok(entry_from(
+        playlist,
+        user_id,
+        file_name,
+        details.followers.map(|followers| followers.total),
+    ))
+}
+
+/// `user_id` is the exported account, which tells owned and followed playlists apart.
+fn entry_from(
+    playlist: Playlist,
+    user_id: &str,
+    file_name: String,
+    followers: Option<u32>,
+) -> PlaylistEntry {
+    PlaylistEntry {
+        followed: playlist.owner.id != user_id,
+        file: file_name,
+        id: playlist.id,
+        name: playlist.name,
+        description: playlist.description.filter(|description| !description.is_empty()),
+        public: playlist.public,
+        collaborative: playlist.collaborative,
+        owner: playlist.owner,
+        snapshot_id: playlist.snapshot_id,
+        followers,
+        images: playlist.images.unwrap_or_default(),
+        cover: None,
+        track_count: None,
+        contributors: Vec::new(),
+    }
+}
+
+/// Picks a file name for each playlist, given as `(id, name)`, that no other
+/// file in the dump uses. Playlists listed in `previous` keep the file they
+/// had there, so names do not depend on the order Spotify lists playlists in.
+/// The files of `unselected` playlists stay in the dump and are never taken.
+pub fn playlist_file_names(
+    playlists: &[(&str, &str)],
+    previous: &[PlaylistEntry],
+    unselected: &[PlaylistEntry],
+    format: Format,
+) -> Vec<String> {
+    let extension = format.extension();
+    let mut taken: HashSet<String> = COMPONENT_FILES
+        .iter()
+        .map(|stem| format!("{}.{}", stem, extension))
+        .chain([MANIFEST_FILE.to_string()])
+        .chain(
+            unselected
+                .iter()
+                .filter(|entry| playlists.iter().all(|(id, _)| *id != entry.id))
+                .map(|entry| entry.file.to_lowercase()),
+        )
+        .collect();
+
+    let kept: Vec<Option<String>> = playlists
+        .iter()
+        .map(|(id, _)| {
+            let entry = previous.iter().find(|entry| entry.id == *id)?;
+            let stem = Path::new(&entry.file).file_stem()?.to_str()?;
+            let file_name = format!("{}.{}", stem, extension);
+            taken.insert(file_name.to_lowercase()).then_some(file_name)
+        })
+        .collect();
+
+    playlists
+        .iter()
+        .zip(kept)
+        .map(|((id, name), kept)| kept.unwrap_or_else(|| playlist_file_name(id, name, format, &mut taken)))
+        .collect()
+}
+
+/// Names that sanitize to nothing (e.g. emoji only) fall back to the playlist
+/// ID, and collisions get the ID appended.
+fn playlist_file_name(id: &str, name: &str, format: Format, taken: &mut HashSet<String>) -> String {
+    let sanitized = sanitize_filename(name);
+    let stem = if sanitized.is_empty() {
+        id.to_string()
+    } else {
+        sanitized
+    };
+
+    // Compare case-insensitively since Windows and macOS file systems do.
+    let extension = format.extension();
+    let mut file_name = format!("{}.{}", stem, extension);
+    if !taken.insert(file_name.to_lowercase()) {
+        file_name = format!("{}_{}.{}", stem, id, extension);
+        taken.insert(file_name.to_lowercase());
+    }
+    file_name
 }
 
 fn sanitize_filename(name: &str) -> String {
-    let sanitized: String = name
-        .chars()
+    name.chars()
         .filter_map(|c| {
             if c.is_alphanumeric() {
                 Some(c)
@@ -145,19 +510,5 @@ fn sanitize_filename(name: &str) -> String {
                 None
             }
         })
-        .collect();
-
-    if sanitized.is_empty() {
-        generate_random_name()
-    } else {
-        sanitized
-    }
-}
-
-fn generate_random_name() -> String {
-    let mut rng = rand::thread_rng();
-    let random_name: String = (0..10)
-        .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
-        .collect();
-    random_name
+        .collect()
 }
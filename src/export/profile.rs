@@ -0,0 +1,29 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::manifest::{Manifest, Profile};
+use crate::types::User;
+use crate::utils;
+
+/// Saves the profile of the exported account into the manifest and returns it.
+pub async fn export_profile(access_token: &str, dump_dir: &Path, force: bool) -> Result<Profile> {
+    let user: User = utils::get_json(access_token, "https://api.spotify.com/v1/me").await?;
+    let profile = Profile::from(user);
+
+    if !force {
+        println!("Dry run: would have saved the profile of {}.", profile.name());
+        return Ok(profile);
+    }
+
+    if !dump_dir.exists() {
+        fs::create_dir_all(dump_dir).context("Failed to create dump directory")?;
+    }
+
+    let mut manifest = Manifest::load(dump_dir)?;
+    manifest.profile = Some(profile.clone());
+    manifest.save(dump_dir)?;
+
+    println!("Profile of {} has been saved to the manifest.", profile.name());
+    Ok(profile)
+}
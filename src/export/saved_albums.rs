@@ -0,0 +1,32 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::format::{Format, SavedAlbumRecord};
+use crate::types::Album;
+use crate::utils;
+
+#[derive(Debug, serde::Deserialize)]
+struct SavedAlbum {
+    added_at: Option<String>,
+    album: Option<Album>,
+}
+
+/// Exports the saved albums into the dump directory and returns what was written.
+pub async fn export_saved_albums(
+    access_token: &str,
+    dump_dir: &Path,
+    format: Format,
+    force: bool,
+) -> Result<Vec<SavedAlbumRecord>> {
+    let albums: Vec<SavedAlbum> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/albums").await?;
+
+    super::export_component(
+        dump_dir,
+        "saved_albums",
+        "saved albums",
+        albums,
+        |saved| SavedAlbumRecord::from_album(saved.added_at, saved.album?),
+        format,
+        force,
+    )
+}
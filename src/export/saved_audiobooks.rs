@@ -0,0 +1,41 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::format::{Format, SavedAudiobookRecord};
+use crate::types::Audiobook;
+use crate::utils;
+
+/// The API reference lists bare audiobooks, but Spotify answers with saved
+/// items like the other library endpoints; both are accepted.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum SavedAudiobook {
+    Saved {
+        added_at: Option<String>,
+        audiobook: Audiobook,
+    },
+    Bare(Audiobook),
+}
+
+/// Exports the saved audiobooks into the dump directory and returns what was written.
+pub async fn export_saved_audiobooks(
+    access_token: &str,
+    dump_dir: &Path,
+    format: Format,
+    force: bool,
+) -> Result<Vec<SavedAudiobookRecord>> {
+    let audiobooks: Vec<SavedAudiobook> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/audiobooks").await?;
+
+    super::export_component(
+        dump_dir,
+        "saved_audiobooks",
+        "saved audiobooks",
+        audiobooks,
+        |saved| match saved {
+            SavedAudiobook::Saved { added_at, audiobook } => SavedAudiobookRecord::from_audiobook(added_at, audiobook),
+            SavedAudiobook::Bare(audiobook) => SavedAudiobookRecord::from_audiobook(None, audiobook),
+        },
+        format,
+        force,
+    )
+}
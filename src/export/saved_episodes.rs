@@ -0,0 +1,32 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::format::{Format, SavedEpisodeRecord};
+use crate::types::Episode;
+use crate::utils;
+
+#[derive(Debug, serde::Deserialize)]
+struct SavedEpisode {
+    added_at: Option<String>,
+    episode: Option<Episode>,
+}
+
+/// Exports the saved episodes into the dump directory and returns what was written.
+pub async fn export_saved_episodes(
+    access_token: &str,
+    dump_dir: &Path,
+    format: Format,
+    force: bool,
+) -> Result<Vec<SavedEpisodeRecord>> {
+    let episodes: Vec<SavedEpisode> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/episodes").await?;
+
+    super::export_component(
+        dump_dir,
+        "saved_episodes",
+        "saved episodes",
+        episodes,
+        |saved| SavedEpisodeRecord::from_episode(saved.added_at, saved.episode?),
+        format,
+        force,
+    )
+}
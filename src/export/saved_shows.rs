@@ -0,0 +1,32 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::format::{Format, SavedShowRecord};
+use crate::types::Show;
+use crate::utils;
+
+#[derive(Debug, serde::Deserialize)]
+struct SavedShow {
+    added_at: Option<String>,
+    show: Option<Show>,
+}
+
+/// Exports the saved shows into the dump directory and returns what was written.
+pub async fn export_saved_shows(
+    access_token: &str,
+    dump_dir: &Path,
+    format: Format,
+    force: bool,
+) -> Result<Vec<SavedShowRecord>> {
+    let shows: Vec<SavedShow> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/shows").await?;
+
+    super::export_component(
+        dump_dir,
+        "saved_shows",
+        "saved shows",
+        shows,
+        |saved| SavedShowRecord::from_show(saved.added_at, saved.show?),
+        format,
+        force,
+    )
+}
@@ -1,94 +1,83 @@
 use anyhow::{Context, Result};
-use csv::Writer;
+use chrono::Utc;
+use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
 
+use crate::format::{self, Format, TrackRecord};
+use crate::manifest::Manifest;
 use crate::types::Track;
 use crate::utils;
 
+const SAVED_TRACKS_URL: &str = "https://api.spotify.com/v1/me/tracks";
+
 #[derive(Debug, serde::Deserialize)]
 struct SavedTrack {
     added_at: Option<String>,
     track: Option<Track>,
 }
 
-pub async fn export_saved_tracks(access_token: &String, force: bool) -> Result<()> {
-    let tracks: Vec<SavedTrack> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/tracks").await?;
+/// Saved tracks of an earlier export, used as the base of an incremental one.
+struct PreviousExport {
+    records: Vec<TrackRecord>,
+    total: u32,
+}
+
+/// Exports the saved tracks into the dump directory and returns what was written.
+///
+/// With a `previous_dir`, only the tracks saved since that dump are fetched
+/// (Spotify lists them newest first) and merged into it. When the total
+/// count shows that tracks were removed in the meantime, the whole library
+/// is fetched again. With a `market`, each track records whether it is
+/// playable there.
+pub async fn export_saved_tracks(
+    access_token: &str,
+    dump_dir: &Path,
+    previous_dir: Option<&Path>,
+    format: Format,
+    market: Option<&str>,
+    force: bool,
+) -> Result<Vec<TrackRecord>> {
+    let previous = match previous_dir {
+        Some(previous_dir) => load_previous(previous_dir, format, market)?,
+        None => None,
+    };
+
+    let incremental = match previous {
+        Some(previous) => fetch_new(access_token, previous, market).await?,
+        None => None,
+    };
+    let (records, total) = match incremental {
+        Some(result) => result,
+        None => {
+            if previous_dir.is_some() {
+                println!("Fetching all saved tracks.");
+            }
+            fetch_saved_tracks(access_token, market).await?
+        }
+    };
 
     if !force {
-        println!("Dry run: would have exported {} saved tracks.", tracks.len());
-        return Ok(());
+        println!("Dry run: would have exported {} saved tracks.", records.len());
+        return Ok(Vec::new());
     }
 
-    let dump_dir = Path::new("dump");
     if !dump_dir.exists() {
-        fs::create_dir(dump_dir).context("Failed to create dump directory")?;
+        fs::create_dir_all(dump_dir).context("Failed to create dump directory")?;
     }
 
-    let output_file = dump_dir.join("saved_tracks.csv");
-    let mut writer = Writer::from_path(&output_file).with_context(|| {
-        format!(
-            "Failed to create CSV file: {}",
-            output_file.to_str().unwrap()
-        )
-    })?;
-
-    writer.write_record(&["Added At", "Track Name", "Artists", "Album", "Id"])?;
-
-    let mut skipped_tracks_count = 0;
-
-    for track in tracks {
-        if let Some(track_data) = track.track {
-            let Track { id, name, artists, album } = track_data;
-
-            if let Some(track_id) = id {
-                let added_at = track
-                    .added_at
-                    .clone()
-                    .unwrap_or_else(|| "Unknown".to_string());
-                let track_name = if name.is_empty() {
-                    "Unknown".to_string()
-                } else {
-                    name
-                };
-                let artist_names: Vec<String> = artists
-                    .into_iter()
-                    .filter_map(|artist| {
-                        let trimmed = artist.name.trim().to_string();
-                        if trimmed.is_empty() {
-                            None
-                        } else {
-                            Some(trimmed)
-                        }
-                    })
-                    .collect();
-                let artists_joined = if artist_names.is_empty() {
-                    "Unknown".to_string()
-                } else {
-                    artist_names.join(", ")
-                };
-                let album_name = if album.name.is_empty() {
-                    "Unknown".to_string()
-                } else {
-                    album.name
-                };
-
-                writer.write_record(&[
-                    added_at.as_str(),
-                    track_name.as_str(),
-                    artists_joined.as_str(),
-                    album_name.as_str(),
-                    track_id.as_str(),
-                ])?;
-            } else {
-                skipped_tracks_count += 1;
-            }
-        } else {
-            skipped_tracks_count += 1;
-        }
-    }
+    let skipped_tracks_count = (total as usize).saturating_sub(records.len());
+
+    let output_file = dump_dir.join(format!("saved_tracks.{}", format.extension()));
+    format::write_tracks(&output_file, format, &records)?;
+
+    let mut manifest = Manifest::load(dump_dir)?;
+    manifest.exported_at = Some(Utc::now());
+    manifest.formats.insert("saved_tracks".to_string(), format);
+    manifest.saved_tracks_total = Some(total);
+    manifest.set_component_market("saved_tracks", market);
+    manifest.save(dump_dir)?;
 
-    writer.flush()?;
     println!(
         "Saved tracks have been exported to {}",
         output_file.to_str().unwrap()
@@ -98,5 +87,93 @@ pub async fn export_saved_tracks(access_token: &String, force: bool) -> Result<(
         println!("Skipped {} saved tracks.", skipped_tracks_count);
     }
 
-    Ok(())
+    Ok(records)
+}
+
+/// Puts newly saved tracks in front of the previous ones. Tracks that were
+/// saved again only keep their new position.
+pub fn merge_saved_tracks(new: Vec<TrackRecord>, previous: Vec<TrackRecord>) -> Vec<TrackRecord> {
+    let new_ids: HashSet<String> = new.iter().map(|record| record.id.clone()).collect();
+    new.into_iter()
+        .chain(previous.into_iter().filter(|record| !new_ids.contains(&record.id)))
+        .collect()
+}
+
+/// Fetches every saved track without writing anything. Also returns the
+/// number of tracks Spotify listed, which includes those that cannot be exported.
+pub async fn fetch_saved_tracks(access_token: &str, market: Option<&str>) -> Result<(Vec<TrackRecord>, u32)> {
+    let url = utils::with_market(SAVED_TRACKS_URL, market);
+    let tracks: Vec<SavedTrack> = utils::get_all_items(access_token, &url).await?;
+    let total = tracks.len() as u32;
+    let records = tracks
+        .into_iter()
+        .filter_map(|saved| TrackRecord::from_track(saved.added_at, saved.track?))
+        .collect();
+    Ok((records, total))
+}
+
+/// Fetches the tracks saved since the previous export and merges them in.
+/// Returns `None` when the result cannot be trusted and a full fetch is needed.
+async fn fetch_new(
+    access_token: &str,
+    previous: PreviousExport,
+    market: Option<&str>,
+) -> Result<Option<(Vec<TrackRecord>, u32)>> {
+    let known: HashSet<(String, Option<String>)> = previous
+        .records
+        .iter()
+        .map(|record| (record.id.clone(), record.added_at.clone()))
+        .collect();
+    let previous_ids: HashSet<&str> = previous.records.iter().map(|record| record.id.as_str()).collect();
+
+    let url = utils::with_market(SAVED_TRACKS_URL, market);
+    let (tracks, total) = utils::get_items_until(access_token, &url, |saved: &SavedTrack| {
+        saved
+            .track
+            .as_ref()
+            .and_then(|track| track.id.clone())
+            .is_some_and(|id| known.contains(&(id, saved.added_at.clone())))
+    })
+    .await?;
+
+    let fetched_count = tracks.len() as u32;
+    let new_records: Vec<TrackRecord> = tracks
+        .into_iter()
+        .filter_map(|saved| TrackRecord::from_track(saved.added_at, saved.track?))
+        .collect();
+    // A track saved again moved to the top, so it was counted in the previous total already.
+    let saved_again = new_records
+        .iter()
+        .filter(|record| previous_ids.contains(record.id.as_str()))
+        .count() as u32;
+
+    if total != previous.total + fetched_count - saved_again {
+        println!("Saved tracks were removed since the previous export.");
+        return Ok(None);
+    }
+
+    println!(
+        "Found {} newly saved tracks; reused {} from the previous export.",
+        new_records.len(),
+        (previous.records.len() as u32).saturating_sub(saved_again)
+    );
+    Ok(Some((merge_saved_tracks(new_records, previous.records), total)))
+}
+
+fn load_previous(previous_dir: &Path, format: Format, market: Option<&str>) -> Result<Option<PreviousExport>> {
+    let manifest = Manifest::load(previous_dir)?;
+    // Dumps written before the total was recorded cannot be checked for removals.
+    let Some(total) = manifest.saved_tracks_total else {
+        return Ok(None);
+    };
+    let file = previous_dir.join(format!("saved_tracks.{}", format.extension()));
+    // Availability checked for another market would be mixed with the new one.
+    if manifest.component_format("saved_tracks") != format || manifest.component_market("saved_tracks") != market || !file.is_file() {
+        return Ok(None);
+    }
+
+    Ok(Some(PreviousExport {
+        records: format::read_tracks(&file)?,
+        total,
+    }))
 }
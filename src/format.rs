@@ -0,0 +1,1039 @@
+use anyhow::{Context, Result};
+use csv::{Reader, WriterBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::types::{Album, Audiobook, Episode, FullArtist, LinkedFrom, Person, Show, Track};
+
+/// File format of the track lists in a dump.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// One row per track; artists are joined with ", ".
+    #[default]
+    Csv,
+    /// A single JSON array of track records.
+    Json,
+    /// One JSON track record per line.
+    Jsonl,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            "jsonl" => Some(Format::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// A track as stored in a dump. This is the schema of the JSON and JSON Lines formats.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackRecord {
+    pub added_at: Option<String>,
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub artists: Vec<ArtistRecord>,
+    #[serde(default)]
+    pub album: AlbumRecord,
+    pub uri: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub explicit: Option<bool>,
+    pub isrc: Option<String>,
+    /// Spotify user ID of whoever added the track. Playlist items only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_by: Option<String>,
+    /// 0-based position in the playlist, as the API counts. Playlist items only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<u32>,
+    /// A local file of whoever added it. It has an empty ID and cannot be
+    /// added to a playlist through the API.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_local: bool,
+    /// Whether the track could be played in the market given to `export --market`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_playable: Option<bool>,
+    /// Why Spotify restricts the track there (`market`, `product`, `explicit`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restriction: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArtistRecord {
+    pub id: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlbumRecord {
+    pub id: Option<String>,
+    pub name: String,
+    pub release_date: Option<String>,
+}
+
+impl TrackRecord {
+    /// Builds a record from an API track. Tracks without an ID (local files,
+    /// unavailable tracks) cannot be restored and yield `None`.
+    pub fn from_track(added_at: Option<String>, track: Track) -> Option<Self> {
+        let Track {
+            id,
+            name,
+            uri,
+            duration_ms,
+            explicit,
+            external_ids,
+            artists,
+            album,
+            is_playable,
+            restrictions,
+            linked_from,
+        } = track;
+
+        // Keep the track that was saved rather than the market's substitute,
+        // so the same track is restored whatever market was checked.
+        let (id, uri) = match linked_from {
+            Some(LinkedFrom { id: Some(original), uri: original_uri }) => (Some(original), original_uri),
+            _ => (id, uri),
+        };
+
+        Some(TrackRecord {
+            added_at,
+            id: id?,
+            name,
+            artists: artists
+                .into_iter()
+                .filter_map(|artist| {
+                    let name = artist.name.trim().to_string();
+                    if name.is_empty() {
+                        None
+                    } else {
+                        Some(ArtistRecord { id: artist.id, name })
+                    }
+                })
+                .collect(),
+            album: AlbumRecord {
+                id: album.id,
+                name: album.name,
+                release_date: album.release_date,
+            },
+            uri,
+            duration_ms,
+            explicit,
+            isrc: external_ids.isrc,
+            added_by: None,
+            position: None,
+            is_local: false,
+            is_playable,
+            restriction: restrictions.and_then(|restrictions| restrictions.reason),
+        })
+    }
+
+    /// Builds a record from a playlist item. Unlike `from_track`, local files
+    /// are kept, with an empty ID, so that the playlist stays complete.
+    pub fn from_playlist_item(
+        added_at: Option<String>,
+        added_by: Option<String>,
+        position: u32,
+        is_local: bool,
+        mut track: Track,
+    ) -> Option<Self> {
+        if is_local {
+            track.id.get_or_insert_with(String::new);
+        }
+        let mut record = Self::from_track(added_at, track)?;
+        record.added_by = added_by;
+        record.position = Some(position);
+        record.is_local = is_local;
+        Some(record)
+    }
+
+    pub fn uri(&self) -> String {
+        self.uri
+            .clone()
+            .unwrap_or_else(|| format!("spotify:track:{}", self.id))
+    }
+}
+
+const TRACK_CSV_HEADERS: [&str; 12] = [
+    "Added At",
+    "Track Name",
+    "Artists",
+    "Album",
+    "Id",
+    "Uri",
+    "Duration Ms",
+    "Added By",
+    "Position",
+    "Is Local",
+    "Playable",
+    "Restriction",
+];
+
+/// Flat CSV row. The first five columns are the layout older dumps use, so
+/// the extra columns are optional when reading. `Added By`, `Position` and
+/// `Is Local` are only filled in for playlist items, `Playable` and
+/// `Restriction` only for exports with a market.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrackCsvRow {
+    #[serde(rename = "Added At")]
+    added_at: String,
+    #[serde(rename = "Track Name")]
+    name: String,
+    #[serde(rename = "Artists")]
+    artists: String,
+    #[serde(rename = "Album")]
+    album: String,
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Uri", default)]
+    uri: Option<String>,
+    #[serde(rename = "Duration Ms", default)]
+    duration_ms: Option<u64>,
+    #[serde(rename = "Added By", default)]
+    added_by: Option<String>,
+    #[serde(rename = "Position", default)]
+    position: Option<u32>,
+    #[serde(rename = "Is Local", default)]
+    is_local: Option<bool>,
+    #[serde(rename = "Playable", default)]
+    is_playable: Option<bool>,
+    #[serde(rename = "Restriction", default)]
+    restriction: Option<String>,
+}
+
+impl From<&TrackRecord> for TrackCsvRow {
+    fn from(record: &TrackRecord) -> Self {
+        TrackCsvRow {
+            added_at: or_unknown(record.added_at.as_deref().unwrap_or_default()),
+            name: or_unknown(&record.name),
+            artists: or_unknown(&join_artists(&record.artists)),
+            album: or_unknown(&record.album.name),
+            id: record.id.clone(),
+            uri: record.uri.clone(),
+            duration_ms: record.duration_ms,
+            added_by: record.added_by.clone(),
+            position: record.position,
+            is_local: record.is_local.then_some(true),
+            is_playable: record.is_playable,
+            restriction: record.restriction.clone(),
+        }
+    }
+}
+
+impl From<TrackCsvRow> for TrackRecord {
+    fn from(row: TrackCsvRow) -> Self {
+        TrackRecord {
+            added_at: from_unknown(row.added_at),
+            id: row.id,
+            name: from_unknown(row.name).unwrap_or_default(),
+            artists: split_artists(row.artists),
+            album: AlbumRecord {
+                name: from_unknown(row.album).unwrap_or_default(),
+                ..Default::default()
+            },
+            uri: row.uri,
+            duration_ms: row.duration_ms,
+            explicit: None,
+            isrc: None,
+            added_by: row.added_by,
+            position: row.position,
+            is_local: row.is_local.unwrap_or_default(),
+            is_playable: row.is_playable,
+            restriction: row.restriction,
+        }
+    }
+}
+
+/// Artists share one CSV column, joined with ", ".
+fn join_artists(artists: &[ArtistRecord]) -> String {
+    artists
+        .iter()
+        .map(|artist| artist.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn split_artists(column: String) -> Vec<ArtistRecord> {
+    from_unknown(column)
+        .map(|artists| {
+            artists
+                .split(", ")
+                .map(|name| ArtistRecord {
+                    id: None,
+                    name: name.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn or_unknown(value: &str) -> String {
+    if value.is_empty() {
+        "Unknown".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn from_unknown(value: String) -> Option<String> {
+    if value.is_empty() || value == "Unknown" {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// A saved album as stored in a dump.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedAlbumRecord {
+    pub added_at: Option<String>,
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub artists: Vec<ArtistRecord>,
+    /// `album`, `single` or `compilation`.
+    pub album_type: Option<String>,
+    pub release_date: Option<String>,
+    pub total_tracks: Option<u32>,
+    pub label: Option<String>,
+    pub upc: Option<String>,
+    pub uri: Option<String>,
+}
+
+impl SavedAlbumRecord {
+    /// Albums without an ID cannot be saved again, so they are skipped.
+    pub fn from_album(added_at: Option<String>, album: Album) -> Option<Self> {
+        Some(SavedAlbumRecord {
+            added_at,
+            id: album.id?,
+            name: album.name,
+            artists: album
+                .artists
+                .into_iter()
+                .map(|artist| ArtistRecord {
+                    id: artist.id,
+                    name: artist.name,
+                })
+                .collect(),
+            album_type: album.album_type,
+            release_date: album.release_date,
+            total_tracks: album.total_tracks,
+            label: album.label,
+            upc: album.external_ids.upc,
+            uri: album.uri,
+        })
+    }
+}
+
+const ALBUM_CSV_HEADERS: [&str; 7] = [
+    "Added At",
+    "Album Name",
+    "Artists",
+    "Release Date",
+    "Total Tracks",
+    "Id",
+    "Uri",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlbumCsvRow {
+    #[serde(rename = "Added At")]
+    added_at: String,
+    #[serde(rename = "Album Name")]
+    name: String,
+    #[serde(rename = "Artists")]
+    artists: String,
+    #[serde(rename = "Release Date")]
+    release_date: String,
+    #[serde(rename = "Total Tracks")]
+    total_tracks: Option<u32>,
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Uri")]
+    uri: Option<String>,
+}
+
+impl From<AlbumCsvRow> for SavedAlbumRecord {
+    fn from(row: AlbumCsvRow) -> Self {
+        SavedAlbumRecord {
+            added_at: from_unknown(row.added_at),
+            id: row.id,
+            name: from_unknown(row.name).unwrap_or_default(),
+            artists: split_artists(row.artists),
+            album_type: None,
+            release_date: from_unknown(row.release_date),
+            total_tracks: row.total_tracks,
+            label: None,
+            upc: None,
+            uri: row.uri,
+        }
+    }
+}
+
+impl Record for SavedAlbumRecord {
+    type CsvRow = AlbumCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &ALBUM_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> AlbumCsvRow {
+        AlbumCsvRow {
+            added_at: or_unknown(self.added_at.as_deref().unwrap_or_default()),
+            name: or_unknown(&self.name),
+            artists: or_unknown(&join_artists(&self.artists)),
+            release_date: or_unknown(self.release_date.as_deref().unwrap_or_default()),
+            total_tracks: self.total_tracks,
+            id: self.id.clone(),
+            uri: self.uri.clone(),
+        }
+    }
+}
+
+/// An artist with its genres, as stored for followed and top artists.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtistProfileRecord {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    pub popularity: Option<u32>,
+    pub followers: Option<u32>,
+    pub uri: Option<String>,
+}
+
+impl ArtistProfileRecord {
+    /// Artists without an ID cannot be looked up or followed again, so they are skipped.
+    pub fn from_artist(artist: FullArtist) -> Option<Self> {
+        Some(ArtistProfileRecord {
+            id: artist.id?,
+            name: artist.name,
+            genres: artist.genres,
+            popularity: artist.popularity,
+            followers: artist.followers.map(|followers| followers.total),
+            uri: artist.uri,
+        })
+    }
+}
+
+const ARTIST_CSV_HEADERS: [&str; 4] = ["Artist Name", "Genres", "Id", "Uri"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArtistCsvRow {
+    #[serde(rename = "Artist Name")]
+    name: String,
+    /// Joined with ", " like the artists of a track.
+    #[serde(rename = "Genres")]
+    genres: String,
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Uri")]
+    uri: Option<String>,
+}
+
+impl From<ArtistCsvRow> for ArtistProfileRecord {
+    fn from(row: ArtistCsvRow) -> Self {
+        ArtistProfileRecord {
+            id: row.id,
+            name: from_unknown(row.name).unwrap_or_default(),
+            genres: if row.genres.is_empty() {
+                Vec::new()
+            } else {
+                row.genres.split(", ").map(String::from).collect()
+            },
+            popularity: None,
+            followers: None,
+            uri: row.uri,
+        }
+    }
+}
+
+impl Record for ArtistProfileRecord {
+    type CsvRow = ArtistCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &ARTIST_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> ArtistCsvRow {
+        ArtistCsvRow {
+            name: or_unknown(&self.name),
+            genres: self.genres.join(", "),
+            id: self.id.clone(),
+            uri: self.uri.clone(),
+        }
+    }
+}
+
+/// A saved podcast show as stored in a dump.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedShowRecord {
+    pub added_at: Option<String>,
+    pub id: String,
+    pub name: String,
+    pub publisher: Option<String>,
+    pub total_episodes: Option<u32>,
+    pub uri: Option<String>,
+}
+
+impl SavedShowRecord {
+    /// Shows without an ID cannot be saved again, so they are skipped.
+    pub fn from_show(added_at: Option<String>, show: Show) -> Option<Self> {
+        Some(SavedShowRecord {
+            added_at,
+            id: show.id?,
+            name: show.name,
+            publisher: show.publisher,
+            total_episodes: show.total_episodes,
+            uri: show.uri,
+        })
+    }
+}
+
+const SHOW_CSV_HEADERS: [&str; 6] = ["Added At", "Show Name", "Publisher", "Total Episodes", "Id", "Uri"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShowCsvRow {
+    #[serde(rename = "Added At")]
+    added_at: String,
+    #[serde(rename = "Show Name")]
+    name: String,
+    #[serde(rename = "Publisher")]
+    publisher: String,
+    #[serde(rename = "Total Episodes")]
+    total_episodes: Option<u32>,
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Uri")]
+    uri: Option<String>,
+}
+
+impl From<ShowCsvRow> for SavedShowRecord {
+    fn from(row: ShowCsvRow) -> Self {
+        SavedShowRecord {
+            added_at: from_unknown(row.added_at),
+            id: row.id,
+            name: from_unknown(row.name).unwrap_or_default(),
+            publisher: from_unknown(row.publisher),
+            total_episodes: row.total_episodes,
+            uri: row.uri,
+        }
+    }
+}
+
+impl Record for SavedShowRecord {
+    type CsvRow = ShowCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &SHOW_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> ShowCsvRow {
+        ShowCsvRow {
+            added_at: or_unknown(self.added_at.as_deref().unwrap_or_default()),
+            name: or_unknown(&self.name),
+            publisher: or_unknown(self.publisher.as_deref().unwrap_or_default()),
+            total_episodes: self.total_episodes,
+            id: self.id.clone(),
+            uri: self.uri.clone(),
+        }
+    }
+}
+
+/// A saved podcast episode as stored in a dump, with the listening progress
+/// Spotify reported at export time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedEpisodeRecord {
+    pub added_at: Option<String>,
+    pub id: String,
+    pub name: String,
+    pub show_id: Option<String>,
+    pub show_name: Option<String>,
+    pub release_date: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub resume_position_ms: Option<u64>,
+    pub fully_played: Option<bool>,
+    pub uri: Option<String>,
+}
+
+impl SavedEpisodeRecord {
+    /// Episodes without an ID cannot be saved again, so they are skipped.
+    pub fn from_episode(added_at: Option<String>, episode: Episode) -> Option<Self> {
+        let (resume_position_ms, fully_played) = episode
+            .resume_point
+            .map(|point| (point.resume_position_ms, point.fully_played))
+            .unwrap_or_default();
+
+        Some(SavedEpisodeRecord {
+            added_at,
+            id: episode.id?,
+            name: episode.name,
+            show_id: episode.show.id,
+            show_name: from_unknown(episode.show.name),
+            release_date: episode.release_date,
+            duration_ms: episode.duration_ms,
+            resume_position_ms,
+            fully_played,
+            uri: episode.uri,
+        })
+    }
+
+    /// Whether the episode had been started or finished when it was exported.
+    pub fn has_progress(&self) -> bool {
+        self.fully_played == Some(true) || self.resume_position_ms.is_some_and(|position| position > 0)
+    }
+}
+
+const EPISODE_CSV_HEADERS: [&str; 9] = [
+    "Added At",
+    "Episode Name",
+    "Show",
+    "Release Date",
+    "Duration Ms",
+    "Resume Position Ms",
+    "Fully Played",
+    "Id",
+    "Uri",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EpisodeCsvRow {
+    #[serde(rename = "Added At")]
+    added_at: String,
+    #[serde(rename = "Episode Name")]
+    name: String,
+    #[serde(rename = "Show")]
+    show_name: String,
+    #[serde(rename = "Release Date")]
+    release_date: String,
+    #[serde(rename = "Duration Ms")]
+    duration_ms: Option<u64>,
+    #[serde(rename = "Resume Position Ms")]
+    resume_position_ms: Option<u64>,
+    #[serde(rename = "Fully Played")]
+    fully_played: Option<bool>,
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Uri")]
+    uri: Option<String>,
+}
+
+impl From<EpisodeCsvRow> for SavedEpisodeRecord {
+    fn from(row: EpisodeCsvRow) -> Self {
+        SavedEpisodeRecord {
+            added_at: from_unknown(row.added_at),
+            id: row.id,
+            name: from_unknown(row.name).unwrap_or_default(),
+            show_id: None,
+            show_name: from_unknown(row.show_name),
+            release_date: from_unknown(row.release_date),
+            duration_ms: row.duration_ms,
+            resume_position_ms: row.resume_position_ms,
+            fully_played: row.fully_played,
+            uri: row.uri,
+        }
+    }
+}
+
+impl Record for SavedEpisodeRecord {
+    type CsvRow = EpisodeCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &EPISODE_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> EpisodeCsvRow {
+        EpisodeCsvRow {
+            added_at: or_unknown(self.added_at.as_deref().unwrap_or_default()),
+            name: or_unknown(&self.name),
+            show_name: or_unknown(self.show_name.as_deref().unwrap_or_default()),
+            release_date: or_unknown(self.release_date.as_deref().unwrap_or_default()),
+            duration_ms: self.duration_ms,
+            resume_position_ms: self.resume_position_ms,
+            fully_played: self.fully_played,
+            id: self.id.clone(),
+            uri: self.uri.clone(),
+        }
+    }
+}
+
+/// A saved audiobook as stored in a dump.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedAudiobookRecord {
+    pub added_at: Option<String>,
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[serde(default)]
+    pub narrators: Vec<String>,
+    pub publisher: Option<String>,
+    pub total_chapters: Option<u32>,
+    pub uri: Option<String>,
+}
+
+impl SavedAudiobookRecord {
+    /// Audiobooks without an ID cannot be saved again, so they are skipped.
+    pub fn from_audiobook(added_at: Option<String>, audiobook: Audiobook) -> Option<Self> {
+        let names = |people: Vec<Person>| -> Vec<String> {
+            people
+                .into_iter()
+                .map(|person| person.name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        };
+
+        Some(SavedAudiobookRecord {
+            added_at,
+            id: audiobook.id?,
+            name: audiobook.name,
+            authors: names(audiobook.authors),
+            narrators: names(audiobook.narrators),
+            publisher: audiobook.publisher,
+            total_chapters: audiobook.total_chapters,
+            uri: audiobook.uri,
+        })
+    }
+}
+
+const AUDIOBOOK_CSV_HEADERS: [&str; 7] = ["Added At", "Title", "Authors", "Narrators", "Publisher", "Id", "Uri"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AudiobookCsvRow {
+    #[serde(rename = "Added At")]
+    added_at: String,
+    #[serde(rename = "Title")]
+    name: String,
+    #[serde(rename = "Authors")]
+    authors: String,
+    #[serde(rename = "Narrators")]
+    narrators: String,
+    #[serde(rename = "Publisher")]
+    publisher: String,
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Uri")]
+    uri: Option<String>,
+}
+
+impl From<AudiobookCsvRow> for SavedAudiobookRecord {
+    fn from(row: AudiobookCsvRow) -> Self {
+        let split = |column: String| -> Vec<String> {
+            from_unknown(column)
+                .map(|names| names.split(", ").map(String::from).collect())
+                .unwrap_or_default()
+        };
+
+        SavedAudiobookRecord {
+            added_at: from_unknown(row.added_at),
+            id: row.id,
+            name: from_unknown(row.name).unwrap_or_default(),
+            authors: split(row.authors),
+            narrators: split(row.narrators),
+            publisher: from_unknown(row.publisher),
+            total_chapters: None,
+            uri: row.uri,
+        }
+    }
+}
+
+impl Record for SavedAudiobookRecord {
+    type CsvRow = AudiobookCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &AUDIOBOOK_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> AudiobookCsvRow {
+        AudiobookCsvRow {
+            added_at: or_unknown(self.added_at.as_deref().unwrap_or_default()),
+            name: or_unknown(&self.name),
+            authors: or_unknown(&self.authors.join(", ")),
+            narrators: or_unknown(&self.narrators.join(", ")),
+            publisher: or_unknown(self.publisher.as_deref().unwrap_or_default()),
+            id: self.id.clone(),
+            uri: self.uri.clone(),
+        }
+    }
+}
+
+/// A play from the recently played history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayRecord {
+    pub played_at: String,
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub artists: Vec<ArtistRecord>,
+    #[serde(default)]
+    pub album: AlbumRecord,
+    pub uri: Option<String>,
+    pub duration_ms: Option<u64>,
+    /// The album, playlist or artist the track was played from.
+    pub context_uri: Option<String>,
+}
+
+impl PlayRecord {
+    pub fn from_play(played_at: String, track: Track, context_uri: Option<String>) -> Option<Self> {
+        let track = TrackRecord::from_track(None, track)?;
+        Some(PlayRecord {
+            played_at,
+            id: track.id,
+            name: track.name,
+            artists: track.artists,
+            album: track.album,
+            uri: track.uri,
+            duration_ms: track.duration_ms,
+            context_uri,
+        })
+    }
+}
+
+const PLAY_CSV_HEADERS: [&str; 7] = ["Played At", "Track Name", "Artists", "Album", "Id", "Uri", "Context Uri"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayCsvRow {
+    #[serde(rename = "Played At")]
+    played_at: String,
+    #[serde(rename = "Track Name")]
+    name: String,
+    #[serde(rename = "Artists")]
+    artists: String,
+    #[serde(rename = "Album")]
+    album: String,
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Uri")]
+    uri: Option<String>,
+    #[serde(rename = "Context Uri")]
+    context_uri: Option<String>,
+}
+
+impl From<PlayCsvRow> for PlayRecord {
+    fn from(row: PlayCsvRow) -> Self {
+        PlayRecord {
+            played_at: row.played_at,
+            id: row.id,
+            name: from_unknown(row.name).unwrap_or_default(),
+            artists: split_artists(row.artists),
+            album: AlbumRecord {
+                name: from_unknown(row.album).unwrap_or_default(),
+                ..Default::default()
+            },
+            uri: row.uri,
+            duration_ms: None,
+            context_uri: row.context_uri,
+        }
+    }
+}
+
+impl Record for PlayRecord {
+    type CsvRow = PlayCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &PLAY_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> PlayCsvRow {
+        PlayCsvRow {
+            played_at: self.played_at.clone(),
+            name: or_unknown(&self.name),
+            artists: or_unknown(&join_artists(&self.artists)),
+            album: or_unknown(&self.album.name),
+            id: self.id.clone(),
+            uri: self.uri.clone(),
+            context_uri: self.context_uri.clone(),
+        }
+    }
+}
+
+/// Extra details about a track, written by `export --enrich`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnrichmentRecord {
+    pub track_id: String,
+    /// Genres of all the track's artists, without duplicates.
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// Popularity (0-100) of the track's first artist.
+    pub artist_popularity: Option<u32>,
+    /// The audio features are missing when Spotify does not provide them.
+    pub tempo: Option<f64>,
+    /// Pitch class of the key (0 = C, 1 = C♯/D♭, ...).
+    pub key: Option<u32>,
+    /// 1 for major, 0 for minor.
+    pub mode: Option<u32>,
+    pub energy: Option<f64>,
+    pub danceability: Option<f64>,
+    pub valence: Option<f64>,
+}
+
+const ENRICHMENT_CSV_HEADERS: [&str; 9] = [
+    "Track Id",
+    "Genres",
+    "Artist Popularity",
+    "Tempo",
+    "Key",
+    "Mode",
+    "Energy",
+    "Danceability",
+    "Valence",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnrichmentCsvRow {
+    #[serde(rename = "Track Id")]
+    track_id: String,
+    /// Joined with ", " like the artists of a track.
+    #[serde(rename = "Genres")]
+    genres: String,
+    #[serde(rename = "Artist Popularity")]
+    artist_popularity: Option<u32>,
+    #[serde(rename = "Tempo")]
+    tempo: Option<f64>,
+    #[serde(rename = "Key")]
+    key: Option<u32>,
+    #[serde(rename = "Mode")]
+    mode: Option<u32>,
+    #[serde(rename = "Energy")]
+    energy: Option<f64>,
+    #[serde(rename = "Danceability")]
+    danceability: Option<f64>,
+    #[serde(rename = "Valence")]
+    valence: Option<f64>,
+}
+
+impl From<EnrichmentCsvRow> for EnrichmentRecord {
+    fn from(row: EnrichmentCsvRow) -> Self {
+        EnrichmentRecord {
+            track_id: row.track_id,
+            genres: if row.genres.is_empty() {
+                Vec::new()
+            } else {
+                row.genres.split(", ").map(String::from).collect()
+            },
+            artist_popularity: row.artist_popularity,
+            tempo: row.tempo,
+            key: row.key,
+            mode: row.mode,
+            energy: row.energy,
+            danceability: row.danceability,
+            valence: row.valence,
+        }
+    }
+}
+
+impl Record for EnrichmentRecord {
+    type CsvRow = EnrichmentCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &ENRICHMENT_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> EnrichmentCsvRow {
+        EnrichmentCsvRow {
+            track_id: self.track_id.clone(),
+            genres: self.genres.join(", "),
+            artist_popularity: self.artist_popularity,
+            tempo: self.tempo,
+            key: self.key,
+            mode: self.mode,
+            energy: self.energy,
+            danceability: self.danceability,
+            valence: self.valence,
+        }
+    }
+}
+
+/// A kind of item stored in its own file of a dump. JSON and JSON Lines hold
+/// the records themselves; CSV holds a flat row per record.
+pub trait Record: Serialize + DeserializeOwned {
+    type CsvRow: Serialize + DeserializeOwned + Into<Self>;
+    /// Written by hand so that empty files still get them.
+    const CSV_HEADERS: &'static [&'static str];
+
+    fn to_csv_row(&self) -> Self::CsvRow;
+}
+
+impl Record for TrackRecord {
+    type CsvRow = TrackCsvRow;
+    const CSV_HEADERS: &'static [&'static str] = &TRACK_CSV_HEADERS;
+
+    fn to_csv_row(&self) -> TrackCsvRow {
+        TrackCsvRow::from(self)
+    }
+}
+
+pub fn write_tracks(path: &Path, format: Format, tracks: &[TrackRecord]) -> Result<()> {
+    write_records(path, format, tracks)
+}
+
+/// Reads a track list, picking the format from the file extension.
+pub fn read_tracks(path: &Path) -> Result<Vec<TrackRecord>> {
+    read_records(path)
+}
+
+pub fn write_records<R: Record>(path: &Path, format: Format, records: &[R]) -> Result<()> {
+    match format {
+        Format::Csv => {
+            let mut writer = WriterBuilder::new()
+                .has_headers(false)
+                .from_path(path)
+                .with_context(|| format!("Failed to create CSV file: {}", path.display()))?;
+            writer.write_record(R::CSV_HEADERS)?;
+            for record in records {
+                writer.serialize(record.to_csv_row())?;
+            }
+            writer.flush()?;
+        }
+        Format::Json => {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create JSON file: {}", path.display()))?;
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer_pretty(&mut writer, records)?;
+            writer.flush()?;
+        }
+        Format::Jsonl => {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create JSON Lines file: {}", path.display()))?;
+            let mut writer = BufWriter::new(file);
+            for record in records {
+                serde_json::to_writer(&mut writer, record)?;
+                writer.write_all(b"\n")?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the records of a dump file, picking the format from the file extension.
+pub fn read_records<R: Record>(path: &Path) -> Result<Vec<R>> {
+    let format = Format::from_path(path)
+        .with_context(|| format!("Unknown dump file format: {}", path.display()))?;
+
+    match format {
+        Format::Csv => {
+            let mut reader = Reader::from_path(path)
+                .with_context(|| format!("Failed to open CSV file: {}", path.display()))?;
+            reader
+                .deserialize::<R::CsvRow>()
+                .map(|row| Ok(row?.into()))
+                .collect()
+        }
+        Format::Json => {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Failed to open JSON file: {}", path.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse JSON file: {}", path.display()))
+        }
+        Format::Jsonl => {
+            let file = File::open(path)
+                .with_context(|| format!("Failed to open JSON Lines file: {}", path.display()))?;
+            let mut records = Vec::new();
+            for (index, line) in BufReader::new(file).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record = serde_json::from_str(&line).with_context(|| {
+                    format!("Failed to parse line {} of {}", index + 1, path.display())
+                })?;
+                records.push(record);
+            }
+            Ok(records)
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use git2::{Commit, ErrorCode, IndexAddOption, Repository, Signature};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::manifest::{Manifest, PlaylistEntry, MANIFEST_FILE};
+
+/// Commits the current state of a dump folder to the git repository kept in
+/// it, creating the repository on first use. Nothing is ever pushed, so no
+/// remote is needed. Returns the commit message, or `None` when nothing changed
+/// since the last commit.
+pub fn commit_export(dump_dir: &Path) -> Result<Option<String>> {
+    let repo = match Repository::open(dump_dir) {
+        Ok(repo) => repo,
+        Err(err) if err.code() == ErrorCode::NotFound => Repository::init(dump_dir).with_context(|| {
+            format!("Failed to create a git repository in {}", dump_dir.display())
+        })?,
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to open the git repository in {}", dump_dir.display()))
+        }
+    };
+
+    let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let previous = match &head {
+        Some(commit) => committed_manifest(&repo, commit)?,
+        None => None,
+    };
+
+    // Stage everything, including deletions, so the commit mirrors the folder.
+    let mut index = repo.index()?;
+    index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+    index.update_all(["*"], None)?;
+    index.write()?;
+    let tree_id = index.write_tree()?;
+    if head.as_ref().map(|commit| commit.tree_id()) == Some(tree_id) {
+        return Ok(None);
+    }
+
+    let tree = repo.find_tree(tree_id)?;
+    // Fall back to a fixed identity when git has no user configured.
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("spoti-dump", "spoti-dump@localhost"))?;
+    let message = summarize(previous.as_ref(), &Manifest::load(dump_dir)?);
+    let parents: Vec<&Commit> = head.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &parents)
+        .context("Failed to commit the export")?;
+
+    Ok(Some(message))
+}
+
+/// Describes how the library changed between two manifests: a subject line,
+/// then one line per added, removed, renamed or resized playlist.
+pub fn summarize(previous: Option<&Manifest>, current: &Manifest) -> String {
+    let date = current
+        .exported_at
+        .map(|exported_at| exported_at.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "of unknown date".to_string());
+
+    let Some(previous) = previous else {
+        let mut subject = format!(
+            "Initial export {}: {}",
+            date,
+            count(current.playlists.len(), "playlist")
+        );
+        if let Some(total) = current.saved_tracks_total {
+            subject.push_str(&format!(", {}", count(total as usize, "saved track")));
+        }
+        return subject;
+    };
+
+    let previous_playlists: HashMap<&str, &PlaylistEntry> = previous
+        .playlists
+        .iter()
+        .map(|entry| (entry.id.as_str(), entry))
+        .collect();
+    let current_ids: Vec<&str> = current.playlists.iter().map(|entry| entry.id.as_str()).collect();
+
+    let mut lines = Vec::new();
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+
+    for entry in &current.playlists {
+        let Some(old) = previous_playlists.get(entry.id.as_str()) else {
+            added += 1;
+            lines.push(format!("Added playlist '{}'{}", entry.name, track_count_suffix(entry)));
+            continue;
+        };
+
+        let mut playlist_changed = false;
+        if old.name != entry.name {
+            playlist_changed = true;
+            lines.push(format!("Renamed playlist '{}' to '{}'", old.name, entry.name));
+        }
+        if let (Some(old_count), Some(new_count)) = (old.track_count, entry.track_count) {
+            if old_count != new_count {
+                playlist_changed = true;
+                lines.push(format!(
+                    "Playlist '{}': {} -> {} tracks",
+                    entry.name, old_count, new_count
+                ));
+            }
+        }
+        if playlist_changed {
+            changed += 1;
+        }
+    }
+
+    for entry in &previous.playlists {
+        if !current_ids.contains(&entry.id.as_str()) {
+            removed += 1;
+            lines.push(format!("Removed playlist '{}'", entry.name));
+        }
+    }
+
+    let playlist_changes: Vec<String> = [(added, "added"), (removed, "removed"), (changed, "changed")]
+        .into_iter()
+        .filter(|(number, _)| *number > 0)
+        .map(|(number, what)| format!("{} {}", number, what))
+        .collect();
+    let mut parts = Vec::new();
+    if !playlist_changes.is_empty() {
+        parts.push(format!("playlists {}", playlist_changes.join(", ")));
+    }
+    if let (Some(old_total), Some(new_total)) = (previous.saved_tracks_total, current.saved_tracks_total) {
+        if old_total != new_total {
+            let difference = new_total as i64 - old_total as i64;
+            parts.push(format!("{:+} saved tracks", difference));
+            lines.push(format!("Saved tracks: {} -> {}", old_total, new_total));
+        }
+    }
+    if parts.is_empty() {
+        parts.push("track lists updated".to_string());
+    }
+
+    let mut message = format!("Export {}: {}", date, parts.join("; "));
+    if !lines.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&lines.join("\n"));
+    }
+    message
+}
+
+fn committed_manifest(repo: &Repository, commit: &Commit) -> Result<Option<Manifest>> {
+    let tree = commit.tree()?;
+    let Some(entry) = tree.get_name(MANIFEST_FILE) else {
+        return Ok(None);
+    };
+    let blob = entry.to_object(repo)?.peel_to_blob()?;
+    // An unreadable old manifest only makes the summary less detailed.
+    Ok(serde_json::from_slice(blob.content()).ok())
+}
+
+fn track_count_suffix(entry: &PlaylistEntry) -> String {
+    entry
+        .track_count
+        .map(|tracks| format!(" ({})", count(tracks as usize, "track")))
+        .unwrap_or_default()
+}
+
+fn count(value: usize, noun: &str) -> String {
+    if value == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", value, noun)
+    }
+}
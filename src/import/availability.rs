@@ -0,0 +1,85 @@
+use anyhow::Result;
+
+use crate::dump::Source;
+use crate::format::TrackRecord;
+use crate::types::User;
+use crate::utils;
+
+/// Most unplayable tracks listed per saved tracks or playlist.
+const LISTED_TRACKS: usize = 10;
+
+/// Tells whether the availability recorded by `export --market` applies to
+/// the account being imported into, which sees the catalog of its country.
+/// Components exported separately may have been checked for different markets.
+pub async fn check_market(access_token: &str, source: &Source) -> Result<()> {
+    let markets = source.read_markets()?;
+    if markets.is_empty() {
+        return Ok(());
+    }
+    // `from_token` was the exported account's country, known if its profile was exported.
+    let mut countries = Vec::new();
+    for market in markets {
+        let country = if market == "from_token" {
+            match source.read_profile()?.and_then(|profile| profile.country) {
+                Some(country) => country,
+                None => {
+                    println!("Track availability in the dump was checked for the exported account's country.");
+                    continue;
+                }
+            }
+        } else {
+            market.to_uppercase()
+        };
+        if !countries.contains(&country) {
+            countries.push(country);
+        }
+    }
+    if countries.is_empty() {
+        return Ok(());
+    }
+
+    let user: User = utils::get_json(access_token, "https://api.spotify.com/v1/me").await?;
+    for market in countries {
+        match &user.country {
+            Some(country) if *country == market => println!(
+                "Track availability in the dump was checked for {}, the country of this account.",
+                market
+            ),
+            Some(country) => println!(
+                "Track availability in the dump was checked for {}, but this account is in {}; \
+                 the tracks reported as unplayable may differ.",
+                market, country
+            ),
+            None => println!("Track availability in the dump was checked for {}.", market),
+        }
+    }
+    Ok(())
+}
+
+/// Lists the tracks the dump recorded as unplayable in its market. They are
+/// still imported, but will show up greyed out there.
+pub fn warn_unplayable<'a>(label: &str, tracks: impl IntoIterator<Item = &'a TrackRecord>) {
+    let unplayable: Vec<&TrackRecord> = tracks
+        .into_iter()
+        .filter(|track| track.is_playable == Some(false))
+        .collect();
+    if unplayable.is_empty() {
+        return;
+    }
+
+    println!(
+        "Warning: {} tracks of {} were not playable in the dump's market:",
+        unplayable.len(),
+        label
+    );
+    for track in unplayable.iter().take(LISTED_TRACKS) {
+        let artists: Vec<&str> = track.artists.iter().map(|artist| artist.name.as_str()).collect();
+        match &track.restriction {
+            Some(reason) => println!("  {} - {} (restricted: {})", artists.join(", "), track.name, reason),
+            None => println!("  {} - {}", artists.join(", "), track.name),
+        }
+    }
+    if unplayable.len() > LISTED_TRACKS {
+        println!("  ...and {} more.", unplayable.len() - LISTED_TRACKS);
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use serde_json::json;
+
+use crate::dump::Source;
+
+pub async fn import_followed_artists(access_token: &str, source: &Source, force: bool) -> Result<()> {
+    let artist_ids: Vec<String> = source
+        .read_followed_artists()?
+        .into_iter()
+        .map(|artist| artist.id)
+        .collect();
+
+    if !force {
+        println!("Dry run: would have followed {} artists.", artist_ids.len());
+        return Ok(());
+    }
+
+    let client = reqwest::Client::new();
+
+    // Spotify accepts at most 50 artist IDs per request.
+    for chunk in artist_ids.chunks(50) {
+        follow_artists(&client, access_token, chunk).await?;
+    }
+
+    println!("All followed artists have been imported.");
+    Ok(())
+}
+
+async fn follow_artists(
+    client: &reqwest::Client,
+    access_token: &str,
+    artist_ids: &[String],
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let response = client
+        .put("https://api.spotify.com/v1/me/following?type=artist")
+        .headers(headers)
+        .json(&json!({ "ids": artist_ids }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to follow artists: {:?}", response));
+    }
+
+    println!("Followed {} artists", artist_ids.len());
+    Ok(())
+}
@@ -1,2 +1,9 @@
+pub mod availability;
+pub mod followed_artists;
 pub mod playlists;
+pub mod profile;
+pub mod saved_albums;
+pub mod saved_audiobooks;
+pub mod saved_episodes;
+pub mod saved_shows;
 pub mod saved_tracks;
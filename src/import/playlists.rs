@@ -1,24 +1,47 @@
-use anyhow::{Result};
-use csv::Reader;
+use anyhow::{Context, Result};
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use serde_json::json;
 use std::fs;
 use std::path::Path;
 
+use crate::cover;
+use crate::dump::{PlaylistDump, Source};
+use crate::import::availability;
+use crate::format::TrackRecord;
+use crate::manifest::PlaylistEntry;
+use crate::select::PlaylistFilter;
 
+pub async fn import_playlists(
+    access_token: &str,
+    user_id: &str,
+    source: &Source,
+    filter: &PlaylistFilter,
+    clone_followed: bool,
+    force: bool,
+) -> Result<()> {
+    let playlists = source.read_playlists()?;
+    let total = playlists.len();
+    let selected: Vec<PlaylistDump> = playlists
+        .into_iter()
+        .filter(|playlist| {
+            let entry = playlist.entry.as_ref();
+            filter.matches(
+                entry.map(|entry| entry.id.as_str()),
+                &playlist.name,
+                entry.map(|entry| entry.owner.id.as_str()),
+            )
+        })
+        .collect();
+    if selected.len() < total {
+        println!(
+            "Skipping {} playlists that do not match the filters.",
+            total - selected.len()
+        );
+    }
 
-pub async fn import_playlists(access_token: &str, user_id: &str, force: bool) -> Result<()> {
-    let dump_dir = Path::new("dump");
-
-    for entry in fs::read_dir(dump_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("csv") {
-            let playlist_name = path.file_stem().unwrap().to_str().unwrap();
-            if playlist_name != "saved_tracks" {
-                import_playlist(access_token, user_id, &path, playlist_name, force).await?;
-            }
-        }
+    for playlist in &selected {
+        let cover = playlist.entry.as_ref().and_then(|entry| source.cover_file(entry));
+        import_playlist(access_token, user_id, playlist, cover.as_deref(), clone_followed, force).await?;
     }
 
     if force {
@@ -27,39 +50,136 @@ pub async fn import_playlists(access_token: &str, user_id: &str, force: bool) ->
     Ok(())
 }
 
+/// Playlists the exported account only followed are followed again, so the
+/// account keeps getting their owner's updates. With `clone_followed`, or when
+/// the original is gone, they are copied like owned playlists instead.
+/// Copies get the `cover` from the dump, if there is one.
 async fn import_playlist(
     access_token: &str,
     user_id: &str,
-    csv_path: &Path,
-    playlist_name: &str,
+    playlist: &PlaylistDump,
+    cover: Option<&Path>,
+    clone_followed: bool,
     force: bool,
 ) -> Result<()> {
-    let mut reader = Reader::from_path(csv_path)?;
-    let track_uris: Vec<String> = reader
-        .records()
-        .filter_map(|result| {
-            result.ok().and_then(|record| {
-                record.get(4).map(|track_id| format!("spotify:track:{}", track_id))
-            })
-        })
-        .collect();
+    let playlist_name = playlist.name.as_str();
+    // Dumps with positions are put back in that order; older ones in file order.
+    let mut tracks: Vec<&TrackRecord> = playlist.tracks.iter().collect();
+    tracks.sort_by_key(|track| track.position);
+    let (local_files, tracks): (Vec<&TrackRecord>, Vec<&TrackRecord>) =
+        tracks.into_iter().partition(|track| track.is_local);
+    let track_uris: Vec<String> = tracks.iter().map(|track| track.uri()).collect();
+    let followed = playlist
+        .entry
+        .as_ref()
+        .filter(|entry| entry.followed && !clone_followed);
+    if followed.is_none() {
+        availability::warn_unplayable(&format!("playlist '{}'", playlist_name), tracks.iter().copied());
+    }
 
     if !force {
-        println!("Dry run: would have imported playlist '{}' with {} tracks.", playlist_name, track_uris.len());
+        match followed {
+            Some(entry) => println!(
+                "Dry run: would have followed playlist '{}' by {}.",
+                playlist_name,
+                owner_name(entry)
+            ),
+            None => println!(
+                "Dry run: would have imported playlist '{}' with {} tracks{}.",
+                playlist_name,
+                track_uris.len(),
+                if cover.is_some() { " and its cover" } else { "" }
+            ),
+        }
         return Ok(());
     }
 
     let client = reqwest::Client::new();
 
+    if let Some(entry) = followed {
+        match follow_playlist(&client, access_token, &entry.id, entry.public.unwrap_or(false)).await {
+            Ok(()) => {
+                println!("Followed playlist '{}' by {}.", playlist_name, owner_name(entry));
+                return Ok(());
+            }
+            Err(err) => println!(
+                "Could not follow playlist '{}' ({}); importing a copy instead.",
+                playlist_name, err
+            ),
+        }
+    }
+
     // Create playlist
-    let playlist_id = create_playlist(&client, access_token, user_id, playlist_name).await?;
+    let playlist_id = create_playlist(&client, access_token, user_id, playlist_name, playlist.entry.as_ref()).await?;
 
     // Add tracks to the playlist in chunks
     for chunk in track_uris.chunks(100) {
         add_tracks_to_playlist(&client, access_token, &playlist_id, chunk).await?;
     }
 
+    // A missing cover is not worth failing the import for.
+    if let Some(cover) = cover {
+        match restore_cover(&client, access_token, &playlist_id, cover).await {
+            Ok(()) => println!("Restored the cover of playlist '{}'.", playlist_name),
+            Err(err) => println!("Could not restore the cover of playlist '{}': {}", playlist_name, err),
+        }
+    }
+
     println!("Playlist '{}' has been imported.", playlist_name);
+    if !local_files.is_empty() {
+        println!(
+            "Left out {} local files of playlist '{}'; Spotify only lets the apps add them.",
+            local_files.len(),
+            playlist_name
+        );
+    }
+    Ok(())
+}
+
+fn owner_name(entry: &PlaylistEntry) -> &str {
+    entry.owner.display_name.as_deref().unwrap_or(&entry.owner.id)
+}
+
+async fn restore_cover(
+    client: &reqwest::Client,
+    access_token: &str,
+    playlist_id: &str,
+    cover_file: &Path,
+) -> Result<()> {
+    let bytes = fs::read(cover_file)
+        .with_context(|| format!("Failed to read cover: {}", cover_file.display()))?;
+    let encoded = cover::encode_for_upload(&bytes)?;
+    cover::upload(client, access_token, playlist_id, encoded).await
+}
+
+async fn follow_playlist(
+    client: &reqwest::Client,
+    access_token: &str,
+    playlist_id: &str,
+    public: bool,
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let url = format!(
+        "https://api.spotify.com/v1/playlists/{}/followers",
+        playlist_id
+    );
+
+    let response = client
+        .put(&url)
+        .headers(headers)
+        .json(&json!({ "public": public }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Spotify answered {}", response.status()));
+    }
     Ok(())
 }
 
@@ -68,6 +188,7 @@ async fn create_playlist(
     access_token: &str,
     user_id: &str,
     playlist_name: &str,
+    entry: Option<&PlaylistEntry>,
 ) -> Result<String> {
     let mut headers = HeaderMap::new();
     headers.insert(
@@ -78,13 +199,22 @@ async fn create_playlist(
 
     let url = format!("https://api.spotify.com/v1/users/{}/playlists", user_id);
 
+    // Dumps without a manifest fall back to a private playlist with a generic description.
+    let description = entry
+        .and_then(|entry| entry.description.as_deref())
+        .unwrap_or("Imported playlist");
+    let collaborative = entry.is_some_and(|entry| entry.collaborative);
+    // Spotify only allows collaborative playlists to be private.
+    let public = !collaborative && entry.and_then(|entry| entry.public).unwrap_or(false);
+
     let response = client
         .post(&url)
         .headers(headers)
         .json(&json!({
             "name": playlist_name,
-            "description": "Imported playlist",
-            "public": false
+            "description": description,
+            "public": public,
+            "collaborative": collaborative
         }))
         .send()
         .await?;
@@ -0,0 +1,34 @@
+use anyhow::Result;
+
+use crate::dump::Source;
+use crate::manifest::Profile;
+use crate::types::User;
+use crate::utils;
+
+/// Profiles cannot be written through the API, so nothing is restored.
+/// Compares the exported account with the one being imported into and
+/// warns when they are in different countries, where the catalog differs.
+pub async fn check_profile(access_token: &str, source: &Source) -> Result<()> {
+    let Some(exported) = source.read_profile()? else {
+        return Ok(());
+    };
+    let user: User = utils::get_json(access_token, "https://api.spotify.com/v1/me").await?;
+    let current = Profile::from(user);
+
+    println!(
+        "Importing the library of {} into the account of {}.",
+        exported.name(),
+        current.name()
+    );
+
+    if let (Some(from), Some(to)) = (&exported.country, &current.country) {
+        if from != to {
+            println!(
+                "Warning: the dump comes from an account in {}, but this account is in {}. \
+                 Tracks that are not available in {} may fail to import or will not play.",
+                from, to, to
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use serde_json::json;
+
+use crate::dump::Source;
+
+pub async fn import_saved_albums(access_token: &str, source: &Source, force: bool) -> Result<()> {
+    let album_ids: Vec<String> = source
+        .read_saved_albums()?
+        .into_iter()
+        .map(|album| album.id)
+        .collect();
+
+    if !force {
+        println!("Dry run: would have imported {} saved albums.", album_ids.len());
+        return Ok(());
+    }
+
+    let client = reqwest::Client::new();
+
+    // Spotify accepts at most 20 album IDs per request.
+    for chunk in album_ids.chunks(20) {
+        save_albums(&client, access_token, chunk).await?;
+    }
+
+    println!("All saved albums have been imported.");
+    Ok(())
+}
+
+async fn save_albums(
+    client: &reqwest::Client,
+    access_token: &str,
+    album_ids: &[String],
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let response = client
+        .put("https://api.spotify.com/v1/me/albums")
+        .headers(headers)
+        .json(&json!({ "ids": album_ids }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to save albums: {:?}", response));
+    }
+
+    println!("Saved {} albums", album_ids.len());
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+use crate::dump::Source;
+
+pub async fn import_saved_audiobooks(access_token: &str, source: &Source, force: bool) -> Result<()> {
+    let audiobook_ids: Vec<String> = source
+        .read_saved_audiobooks()?
+        .into_iter()
+        .map(|audiobook| audiobook.id)
+        .collect();
+
+    if !force {
+        println!("Dry run: would have imported {} saved audiobooks.", audiobook_ids.len());
+        return Ok(());
+    }
+
+    let client = reqwest::Client::new();
+
+    // Spotify accepts at most 50 audiobook IDs per request.
+    for chunk in audiobook_ids.chunks(50) {
+        save_audiobooks(&client, access_token, chunk).await?;
+    }
+
+    println!("All saved audiobooks have been imported.");
+    Ok(())
+}
+
+async fn save_audiobooks(
+    client: &reqwest::Client,
+    access_token: &str,
+    audiobook_ids: &[String],
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+
+    // Unlike the other library endpoints, this one only takes the IDs in the query.
+    let url = format!("https://api.spotify.com/v1/me/audiobooks?ids={}", audiobook_ids.join(","));
+
+    let response = client.put(&url).headers(headers).send().await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to save audiobooks: {:?}", response));
+    }
+
+    println!("Saved {} audiobooks", audiobook_ids.len());
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use serde_json::json;
+
+use crate::dump::Source;
+
+/// Saves the episodes again. Spotify has no endpoint to set listening
+/// progress, so resume points and the played state stay in the dump.
+pub async fn import_saved_episodes(access_token: &str, source: &Source, force: bool) -> Result<()> {
+    let episodes = source.read_saved_episodes()?;
+    let with_progress = episodes.iter().filter(|episode| episode.has_progress()).count();
+    let episode_ids: Vec<String> = episodes.into_iter().map(|episode| episode.id).collect();
+
+    if !force {
+        println!("Dry run: would have imported {} saved episodes.", episode_ids.len());
+    } else {
+        let client = reqwest::Client::new();
+
+        // Spotify accepts at most 50 episode IDs per request.
+        for chunk in episode_ids.chunks(50) {
+            save_episodes(&client, access_token, chunk).await?;
+        }
+
+        println!("All saved episodes have been imported.");
+    }
+
+    if with_progress > 0 {
+        println!(
+            "Listening progress of {} episodes cannot be restored; it is kept in the dump.",
+            with_progress
+        );
+    }
+    Ok(())
+}
+
+async fn save_episodes(
+    client: &reqwest::Client,
+    access_token: &str,
+    episode_ids: &[String],
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let response = client
+        .put("https://api.spotify.com/v1/me/episodes")
+        .headers(headers)
+        .json(&json!({ "ids": episode_ids }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to save episodes: {:?}", response));
+    }
+
+    println!("Saved {} episodes", episode_ids.len());
+    Ok(())
+}
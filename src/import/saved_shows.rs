@@ -0,0 +1,51 @@
+use anyhow::Result;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+use crate::dump::Source;
+
+pub async fn import_saved_shows(access_token: &str, source: &Source, force: bool) -> Result<()> {
+    let show_ids: Vec<String> = source
+        .read_saved_shows()?
+        .into_iter()
+        .map(|show| show.id)
+        .collect();
+
+    if !force {
+        println!("Dry run: would have imported {} saved shows.", show_ids.len());
+        return Ok(());
+    }
+
+    let client = reqwest::Client::new();
+
+    // Spotify accepts at most 50 show IDs per request.
+    for chunk in show_ids.chunks(50) {
+        save_shows(&client, access_token, chunk).await?;
+    }
+
+    println!("All saved shows have been imported.");
+    Ok(())
+}
+
+async fn save_shows(
+    client: &reqwest::Client,
+    access_token: &str,
+    show_ids: &[String],
+) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+
+    // Unlike the other library endpoints, this one only takes the IDs in the query.
+    let url = format!("https://api.spotify.com/v1/me/shows?ids={}", show_ids.join(","));
+
+    let response = client.put(&url).headers(headers).send().await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to save shows: {:?}", response));
+    }
+
+    println!("Saved {} shows", show_ids.len());
+    Ok(())
+}
@@ -1,26 +1,14 @@
-use anyhow::{Context, Result};
-use csv::Reader;
+use anyhow::Result;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use serde_json::json;
-use std::path::Path;
 
+use crate::dump::Source;
+use crate::import::availability;
 
-
-pub async fn import_saved_tracks(access_token: &str, force: bool) -> Result<()> {
-    let dump_dir = Path::new("dump");
-    let input_file = dump_dir.join("saved_tracks.csv");
-
-    let mut reader = Reader::from_path(&input_file)
-        .with_context(|| format!("Failed to open CSV file: {}", input_file.to_str().unwrap()))?;
-
-    let track_ids: Vec<String> = reader
-        .records()
-        .filter_map(|result| {
-            result.ok().and_then(|record| {
-                record.get(4).map(|track_id| track_id.to_string())
-            })
-        })
-        .collect();
+pub async fn import_saved_tracks(access_token: &str, source: &Source, force: bool) -> Result<()> {
+    let tracks = source.read_saved_tracks()?;
+    availability::warn_unplayable("the saved tracks", &tracks);
+    let track_ids: Vec<String> = tracks.into_iter().map(|track| track.id).collect();
 
     if !force {
         println!("Dry run: would have imported {} saved tracks.", track_ids.len());
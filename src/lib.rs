@@ -1,12 +1,34 @@
 pub mod access_token;
+pub mod archive;
+pub mod convert;
+pub mod cover;
+pub mod crypto;
+pub mod diff;
+pub mod dump;
 pub mod export;
+pub mod format;
+pub mod history;
 pub mod import;
+pub mod manifest;
 pub mod purge;
+pub mod select;
+pub mod snapshot;
+pub mod sqlite;
+pub mod status;
 pub mod types;
 pub mod utils;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+use convert::PlaylistFormat;
+use dump::{DumpDir, Source};
+use format::{Format, TrackRecord};
+use manifest::Manifest;
+use select::{Component, Selection};
+use snapshot::Retention;
+use sqlite::Library;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -20,14 +42,117 @@ pub enum Commands {
     Export {
         #[clap(long, action)]
         force: bool,
+        /// File format of the exported track lists
+        #[clap(long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+        /// Also upsert the library into this SQLite database
+        #[clap(long)]
+        sqlite: Option<PathBuf>,
+        /// Also write each playlist for media players (comma separated)
+        #[clap(long, value_enum, value_delimiter = ',')]
+        playlist_format: Vec<PlaylistFormat>,
+        /// Also bundle the dump into a single .tar.zst or .zip archive with checksums
+        #[clap(long)]
+        archive: Option<PathBuf>,
+        /// Encrypt the archive with a passphrase (read from SPOTI_DUMP_PASSPHRASE or prompted); no plaintext dump is kept
+        #[clap(long, action, requires = "archive", conflicts_with_all = ["snapshot", "incremental", "git"])]
+        encrypt: bool,
+        /// Write into a new timestamped subfolder of dump/ instead of overwriting it
+        #[clap(long, action)]
+        snapshot: bool,
+        /// Only fetch what changed since the previous dump: new saved tracks and changed playlists
+        #[clap(long, action)]
+        incremental: bool,
+        /// Keep dump/ as a local git repository and commit each export with a summary of the changes
+        #[clap(long, action, conflicts_with = "snapshot")]
+        git: bool,
+        /// Also fetch artist genres and popularity and track audio features into enrichment.<format>
+        #[clap(long, action)]
+        enrich: bool,
+        /// Record whether each track is playable in this market: a country code such as SE, or from_token for the account's country
+        #[clap(long)]
+        market: Option<String>,
+        #[clap(flatten)]
+        select: Selection,
     },
     Import {
         #[clap(long, action)]
         force: bool,
+        /// Dump folder or .tar.zst/.zip archive to import
+        #[clap(long, default_value = "dump")]
+        input: PathBuf,
+        /// File format of the dump; defaults to the one recorded in its manifest
+        #[clap(long, value_enum)]
+        format: Option<Format>,
+        /// Read the library from this SQLite database instead of the dump folder
+        #[clap(long, conflicts_with_all = ["format", "input"])]
+        sqlite: Option<PathBuf>,
+        /// Copy playlists owned by other users instead of following them again
+        #[clap(long, action)]
+        clone_followed: bool,
+        #[clap(flatten)]
+        select: Selection,
     },
     Purge {
         #[clap(long, action)]
         force: bool,
+        #[clap(flatten)]
+        select: Selection,
+    },
+    /// Compare the live account with a dump, without changing anything
+    Status {
+        /// Dump folder or .tar.zst/.zip archive to compare with
+        #[clap(long, default_value = "dump")]
+        input: PathBuf,
+        /// File format of the dump; defaults to the one recorded in its manifest
+        #[clap(long, value_enum)]
+        format: Option<Format>,
+        /// Print the differences as JSON
+        #[clap(long, action)]
+        json: bool,
+    },
+    /// Convert the playlists of an existing dump for media players, without contacting Spotify
+    Convert {
+        #[clap(long, action)]
+        force: bool,
+        /// Playlist formats to write (comma separated)
+        #[clap(long, value_enum, value_delimiter = ',', required = true)]
+        to: Vec<PlaylistFormat>,
+        /// Dump folder or archive to convert
+        #[clap(long, default_value = "dump")]
+        input: PathBuf,
+        /// Folder that receives one subfolder per format; defaults to the dump folder (its latest snapshot, if it holds snapshots)
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Check an archive against the checksums in its manifest, without contacting Spotify
+    Verify {
+        archive: PathBuf,
+    },
+    /// Compare two dumps (folders or archives) and report what changed from the first to the second
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Print the differences as JSON
+        #[clap(long, action)]
+        json: bool,
+    },
+    /// Delete old snapshots written by `export --snapshot`, keeping the ones the rules select
+    Prune {
+        #[clap(long, action)]
+        force: bool,
+        /// Folder holding the snapshots
+        #[clap(long, default_value = "dump")]
+        dir: PathBuf,
+        /// Keep the newest snapshot of each of the last N days
+        #[clap(long, default_value_t = 0)]
+        keep_daily: usize,
+        /// Keep the newest snapshot of each of the last N weeks
+        #[clap(long, default_value_t = 0)]
+        keep_weekly: usize,
+        /// Keep the newest snapshot of each of the last N months
+        #[clap(long, default_value_t = 0)]
+        keep_monthly: usize,
     },
 }
 
@@ -35,22 +160,249 @@ pub async fn run() -> Result<()> {
     let cli = Cli::parse();
 
     if let Some(command) = &cli.command {
+        // Offline commands work on existing dumps and never need to authorize.
+        match command {
+            Commands::Convert { force, to, input, output } => {
+                if !*force {
+                    println!("This is a dry run. No playlists will be written.");
+                    println!("Use the --force flag to write playlists.");
+                }
+                let dump_dir = DumpDir::open(input)?;
+                let output = match output {
+                    Some(output) => output.as_path(),
+                    None if dump_dir.is_archive() => {
+                        return Err(anyhow::anyhow!(
+                            "Use --output to choose where to write playlists converted from an archive."
+                        ))
+                    }
+                    None => dump_dir.path(),
+                };
+                let source = Source::Dir {
+                    path: dump_dir.path().to_path_buf(),
+                    format: None,
+                };
+                convert::convert_dump(&source, output, to, *force)?;
+                return Ok(());
+            }
+            Commands::Verify { archive } => {
+                let verification = archive::verify(archive)?;
+                for file in &verification.missing {
+                    println!("Missing: {}", file);
+                }
+                for file in &verification.mismatched {
+                    println!("Checksum mismatch: {}", file);
+                }
+                for file in &verification.unlisted {
+                    println!("Not in manifest: {}", file);
+                }
+                if !verification.is_ok() {
+                    return Err(anyhow::anyhow!("Archive {} is corrupted or incomplete.", archive.display()));
+                }
+                println!("Archive is intact: {} files verified.", verification.verified);
+                return Ok(());
+            }
+            Commands::Diff { old, new, json } => {
+                let old_dir = DumpDir::open(old)?;
+                let new_dir = DumpDir::open(new)?;
+                let old_source = Source::Dir {
+                    path: old_dir.path().to_path_buf(),
+                    format: None,
+                };
+                let new_source = Source::Dir {
+                    path: new_dir.path().to_path_buf(),
+                    format: None,
+                };
+                let differences = diff::diff(
+                    &old_source.read_saved_tracks()?,
+                    &old_source.read_playlists()?,
+                    &new_source.read_saved_tracks()?,
+                    &new_source.read_playlists()?,
+                );
+                if *json {
+                    println!("{}", serde_json::to_string_pretty(&differences)?);
+                } else {
+                    differences.print();
+                }
+                return Ok(());
+            }
+            Commands::Prune { force, dir, keep_daily, keep_weekly, keep_monthly } => {
+                let retention = Retention {
+                    daily: *keep_daily,
+                    weekly: *keep_weekly,
+                    monthly: *keep_monthly,
+                };
+                if retention.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "Specify at least one of --keep-daily, --keep-weekly or --keep-monthly."
+                    ));
+                }
+                if !*force {
+                    println!("This is a dry run. No snapshots will be deleted.");
+                    println!("Use the --force flag to delete snapshots.");
+                }
+                snapshot::prune(dir, retention, *force)?;
+                return Ok(());
+            }
+            _ => {}
+        }
+
         let access_token = access_token::get_access_token(command.clone()).await?;
 
         match command {
-            Commands::Export { force } => {
+            Commands::Export { force, format, sqlite, playlist_format, archive, encrypt, snapshot, incremental, git, enrich, market, select } => {
                 if !*force {
                     println!("This is a dry run. No tracks will be exported.");
                     println!("Use the --force flag to export tracks.");
                 }
+                // Ask up front so the export does not stall on a prompt at the very end.
+                let passphrase = if *encrypt && *force {
+                    Some(crypto::passphrase(true)?)
+                } else {
+                    None
+                };
+                let dump_root = Path::new("dump");
+                // Encrypted exports are staged in a temporary folder that is removed
+                // once archived, so no plaintext copy is left in dump/.
+                let staging = if *encrypt && *force {
+                    Some(tempfile::tempdir().context("Failed to create a temporary directory")?)
+                } else {
+                    None
+                };
+                let dump_dir = if let Some(staging) = &staging {
+                    staging.path().to_path_buf()
+                } else if *snapshot {
+                    snapshot::new_snapshot_dir(dump_root)
+                } else {
+                    dump_root.to_path_buf()
+                };
+                let dump_dir = dump_dir.as_path();
+                // A snapshot export builds on the latest snapshot; an in-place export
+                // reuses the files of dump/ itself, even if it also holds snapshots.
+                let previous_dir = if !*incremental || !dump_root.is_dir() {
+                    None
+                } else if *snapshot {
+                    Some(snapshot::resolve(dump_root)?)
+                } else {
+                    Some(dump_root.to_path_buf())
+                };
+
+                let user_id = utils::get_user_id(&access_token).await?;
+
                 println!("Exporting tracks and playlists...");
-                export::saved_tracks::export_saved_tracks(&access_token, *force).await?;
-                export::playlists::export_playlists(&access_token, *force).await?;
+                if select.includes(Component::Profile) {
+                    export::profile::export_profile(&access_token, dump_dir, *force).await?;
+                }
+                let saved_tracks = if select.includes(Component::SavedTracks) {
+                    Some(export::saved_tracks::export_saved_tracks(&access_token, dump_dir, previous_dir.as_deref(), *format, market.as_deref(), *force).await?)
+                } else {
+                    None
+                };
+                if select.includes(Component::SavedAlbums) {
+                    export::saved_albums::export_saved_albums(&access_token, dump_dir, *format, *force).await?;
+                }
+                if select.includes(Component::FollowedArtists) {
+                    export::followed_artists::export_followed_artists(&access_token, dump_dir, *format, *force).await?;
+                }
+                if select.includes(Component::SavedShows) {
+                    export::saved_shows::export_saved_shows(&access_token, dump_dir, *format, *force).await?;
+                }
+                if select.includes(Component::SavedEpisodes) {
+                    export::saved_episodes::export_saved_episodes(&access_token, dump_dir, *format, *force).await?;
+                }
+                if select.includes(Component::SavedAudiobooks) {
+                    export::saved_audiobooks::export_saved_audiobooks(&access_token, dump_dir, *format, *force).await?;
+                }
+                if select.includes(Component::ListeningHistory) {
+                    // Outside the snapshots, so pruning them keeps the history. Encrypted
+                    // exports keep it in the archive instead.
+                    let history_root = if staging.is_some() { dump_dir } else { dump_root };
+                    let history_dir = history_root.join(export::listening_history::HISTORY_DIR);
+                    export::listening_history::export_listening_history(&access_token, &history_dir, *format, *force).await?;
+                }
+                let playlists = if select.includes(Component::Playlists) {
+                    Some(export::playlists::export_playlists(&access_token, &user_id, dump_dir, previous_dir.as_deref(), *format, market.as_deref(), &select.playlists, *force).await?)
+                } else {
+                    None
+                };
+                if *enrich {
+                    let tracks: Vec<&TrackRecord> = saved_tracks
+                        .iter()
+                        .flatten()
+                        .chain(playlists.iter().flatten().flat_map(|playlist| playlist.tracks.iter()))
+                        .collect();
+                    export::enrichment::export_enrichment(&access_token, dump_dir, &tracks, *format, *force).await?;
+                }
+                if let Some(path) = sqlite {
+                    if *force {
+                        let mut library = Library::open(path)?;
+                        if let Some(saved_tracks) = &saved_tracks {
+                            library.write_saved_tracks(saved_tracks)?;
+                        }
+                        if let Some(playlists) = &playlists {
+                            library.write_playlists(playlists)?;
+                        }
+                        println!("Library has been exported to {}", path.display());
+                    } else {
+                        println!("Dry run: would have exported the library to {}", path.display());
+                    }
+                }
+                if let (Some(playlists), false) = (&playlists, playlist_format.is_empty()) {
+                    if *force {
+                        convert::write_playlists(playlists, dump_dir, playlist_format, true)?;
+                    } else {
+                        println!("Dry run: would have written the playlists for media players.");
+                    }
+                }
+
+                if *force {
+                    let mut manifest = Manifest::load(dump_dir)?;
+                    manifest.account_id = Some(user_id);
+                    manifest.tool_version = Some(env!("CARGO_PKG_VERSION").to_string());
+                    manifest.save(dump_dir)?;
+                }
+                if let Some(path) = archive {
+                    if *force {
+                        match (staging, &passphrase) {
+                            (Some(staging), Some(passphrase)) => {
+                                let encrypted = crypto::seal_dump(staging, path, passphrase)?;
+                                println!("Dump has been archived and encrypted to {}", encrypted.display());
+                            }
+                            _ => {
+                                archive::create(dump_dir, path)?;
+                                println!("Dump has been archived to {}", path.display());
+                            }
+                        }
+                    } else {
+                        println!("Dry run: would have archived the dump to {}", path.display());
+                    }
+                }
+                if *snapshot {
+                    if *force {
+                        snapshot::mark_latest(dump_root, dump_dir)?;
+                        println!("Snapshot {} is now the latest.", dump_dir.display());
+                    } else {
+                        println!("Dry run: would have written a new snapshot in {}", dump_root.display());
+                    }
+                }
+                if *git {
+                    if *force {
+                        match history::commit_export(dump_dir)? {
+                            Some(message) => println!(
+                                "Committed the export to {}: {}",
+                                dump_dir.display(),
+                                message.lines().next().unwrap_or_default()
+                            ),
+                            None => println!("Nothing changed since the last committed export."),
+                        }
+                    } else {
+                        println!("Dry run: would have committed the export to the git repository in {}", dump_dir.display());
+                    }
+                }
                 if *force {
                     println!("Export completed successfully.");
                 }
             }
-            Commands::Import { force } => {
+            Commands::Import { force, input, format, sqlite, clone_followed, select } => {
                 if !*force {
                     println!("This is a dry run. No tracks will be imported.");
                     println!("Use the --force flag to import tracks.");
@@ -58,26 +410,97 @@ pub async fn run() -> Result<()> {
                 let user_id = utils::get_user_id(&access_token).await?;
                 println!("Retrieved user ID: {}", user_id);
 
+                // `_dump_dir` keeps an extracted archive alive until the import is done.
+                let (source, _dump_dir) = match sqlite {
+                    Some(path) => (Source::Sqlite(path.clone()), None),
+                    None => {
+                        let dump_dir = DumpDir::open(input)?;
+                        let source = Source::Dir {
+                            path: dump_dir.path().to_path_buf(),
+                            format: *format,
+                        };
+                        (source, Some(dump_dir))
+                    }
+                };
+
+                if select.includes(Component::Profile) {
+                    import::profile::check_profile(&access_token, &source).await?;
+                }
+                if select.includes(Component::SavedTracks) || select.includes(Component::Playlists) {
+                    import::availability::check_market(&access_token, &source).await?;
+                }
+
                 println!("Importing tracks and playlists...");
-                import::saved_tracks::import_saved_tracks(&access_token, *force).await?;
-                import::playlists::import_playlists(&access_token, &user_id, *force).await?;
+                if select.includes(Component::SavedTracks) {
+                    import::saved_tracks::import_saved_tracks(&access_token, &source, *force).await?;
+                }
+                if select.includes(Component::SavedAlbums) {
+                    import::saved_albums::import_saved_albums(&access_token, &source, *force).await?;
+                }
+                if select.includes(Component::FollowedArtists) {
+                    import::followed_artists::import_followed_artists(&access_token, &source, *force).await?;
+                }
+                if select.includes(Component::SavedShows) {
+                    import::saved_shows::import_saved_shows(&access_token, &source, *force).await?;
+                }
+                if select.includes(Component::SavedEpisodes) {
+                    import::saved_episodes::import_saved_episodes(&access_token, &source, *force).await?;
+                }
+                if select.includes(Component::SavedAudiobooks) {
+                    import::saved_audiobooks::import_saved_audiobooks(&access_token, &source, *force).await?;
+                }
+                if select.includes(Component::Playlists) {
+                    import::playlists::import_playlists(&access_token, &user_id, &source, &select.playlists, *clone_followed, *force).await?;
+                }
                 if *force {
                     println!("Import completed successfully.");
                 }
             }
-            Commands::Purge { force } => {
+            Commands::Purge { force, select } => {
                 if !*force {
                     println!("This is a dry run. No tracks will be deleted.");
                     println!("Use the --force flag to delete tracks.");
                 }
 
                 println!("Purging tracks and playlists...");
-                let _ = purge::saved_tracks::purge_saved_tracks(&access_token, *force).await?;
-                let _ = purge::playlists::purge_playlists(&access_token, *force).await?;
+                if select.includes(Component::SavedTracks) {
+                    let _ = purge::saved_tracks::purge_saved_tracks(&access_token, *force).await?;
+                }
+                if select.includes(Component::SavedAlbums) {
+                    let _ = purge::saved_albums::purge_saved_albums(&access_token, *force).await?;
+                }
+                if select.includes(Component::SavedShows) {
+                    let _ = purge::saved_shows::purge_saved_shows(&access_token, *force).await?;
+                }
+                if select.includes(Component::SavedEpisodes) {
+                    let _ = purge::saved_episodes::purge_saved_episodes(&access_token, *force).await?;
+                }
+                if select.includes(Component::SavedAudiobooks) {
+                    let _ = purge::saved_audiobooks::purge_saved_audiobooks(&access_token, *force).await?;
+                }
+                if select.includes(Component::Playlists) {
+                    let _ = purge::playlists::purge_playlists(&access_token, &select.playlists, *force).await?;
+                }
                 if *force {
                     println!("Purge completed successfully.");
                 }
             }
+            Commands::Status { input, format, json } => {
+                let dump_dir = DumpDir::open(input)?;
+                let source = Source::Dir {
+                    path: dump_dir.path().to_path_buf(),
+                    format: *format,
+                };
+                let differences = status::status(&access_token, &source).await?;
+                if *json {
+                    println!("{}", serde_json::to_string_pretty(&differences)?);
+                } else {
+                    status::print_status(&differences);
+                }
+            }
+            Commands::Convert { .. } | Commands::Verify { .. } | Commands::Diff { .. } | Commands::Prune { .. } => {
+                unreachable!("offline commands return before authorizing")
+            }
         }
     } else {
         println!("No command specified. Use --help for usage information.");
@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::format::Format;
+use crate::types::{ExplicitContent, Image, Owner, User};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Bumped whenever the dump layout changes in a way older versions cannot read.
+/// 2: track lists may be JSON or JSON Lines, each component records its own
+/// format, and dump folders may hold snapshots.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub exported_at: Option<DateTime<Utc>>,
+    /// Spotify user ID of the exported account.
+    pub account_id: Option<String>,
+    /// Version of spoti-dump that wrote the dump.
+    pub tool_version: Option<String>,
+    /// Format of the playlist files, and of the component files of dumps
+    /// written before `formats` existed. Those predating the format option are CSV.
+    #[serde(default)]
+    pub format: Format,
+    /// Format of each component file (`saved_tracks`, `saved_albums`, ...),
+    /// which can differ when components were exported separately.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub formats: BTreeMap<String, Format>,
+    #[serde(default)]
+    pub playlists: Vec<PlaylistEntry>,
+    /// Number of saved tracks Spotify reported at export time, including
+    /// those that could not be exported. Used by incremental exports to
+    /// detect removals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved_tracks_total: Option<u32>,
+    /// Market the track availability of each component (`saved_tracks`,
+    /// `playlists`) was checked for (`export --market`), either a country
+    /// code or `from_token`. Components exported without a market are absent.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub markets: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+    /// SHA-256 of every other file in the dump, keyed by `/`-separated path.
+    /// Only filled in when the dump is bundled into an archive.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checksums: BTreeMap<String, String>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            exported_at: None,
+            account_id: None,
+            tool_version: None,
+            format: Format::Csv,
+            formats: BTreeMap::new(),
+            playlists: Vec::new(),
+            saved_tracks_total: None,
+            markets: BTreeMap::new(),
+            profile: None,
+            checksums: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    pub file: String,
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub public: Option<bool>,
+    #[serde(default)]
+    pub collaborative: bool,
+    #[serde(default)]
+    pub owner: Owner,
+    pub snapshot_id: Option<String>,
+    pub followers: Option<u32>,
+    /// Owned by another user; the exported account only follows it.
+    #[serde(default)]
+    pub followed: bool,
+    #[serde(default)]
+    pub images: Vec<Image>,
+    /// Downloaded custom cover, relative to the dump folder (e.g. `covers/Road_Trip.jpg`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<String>,
+    /// Number of tracks written to the playlist's file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_count: Option<u32>,
+    /// Who added how many tracks, most first. Collaborative playlists only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributors: Vec<Contributor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contributor {
+    /// Spotify user ID.
+    pub user_id: String,
+    pub tracks: u32,
+}
+
+/// Profile of the exported account at export time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub display_name: Option<String>,
+    /// ISO 3166-1 alpha-2 code; decides which tracks are available.
+    pub country: Option<String>,
+    /// Subscription level: `premium`, `free`, ...
+    pub product: Option<String>,
+    pub followers: Option<u32>,
+    #[serde(default)]
+    pub images: Vec<Image>,
+    pub explicit_content: Option<ExplicitContent>,
+}
+
+impl Profile {
+    /// The display name, or the user ID for accounts without one.
+    pub fn name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+impl From<User> for Profile {
+    fn from(user: User) -> Self {
+        Profile {
+            id: user.id,
+            display_name: user.display_name,
+            country: user.country,
+            product: user.product,
+            followers: user.followers.map(|followers| followers.total),
+            images: user.images,
+            explicit_content: user.explicit_content,
+        }
+    }
+}
+
+impl Manifest {
+    /// Loads the manifest from a dump directory. Dumps written before the
+    /// manifest existed have none, in which case an empty one is returned.
+    pub fn load(dump_dir: &Path) -> Result<Self> {
+        let path = dump_dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read manifest: {}", path.display()))?;
+        let manifest: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse manifest: {}", path.display()))?;
+
+        if manifest.schema_version > SCHEMA_VERSION {
+            return Err(anyhow::anyhow!(
+                "Dump schema version {} is newer than the supported version {}. Please update spoti-dump.",
+                manifest.schema_version,
+                SCHEMA_VERSION
+            ));
+        }
+
+        Ok(manifest)
+    }
+
+    /// Writes the manifest with the current schema version, since updating
+    /// an older dump may add what its version did not have.
+    pub fn save(&self, dump_dir: &Path) -> Result<()> {
+        let path = dump_dir.join(MANIFEST_FILE);
+        let manifest = Manifest {
+            schema_version: SCHEMA_VERSION,
+            ..self.clone()
+        };
+        let contents = serde_json::to_string_pretty(&manifest)?;
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write manifest: {}", path.display()))
+    }
+
+    /// Format of a component file such as `saved_tracks`.
+    pub fn component_format(&self, stem: &str) -> Format {
+        self.formats.get(stem).copied().unwrap_or(self.format)
+    }
+
+    /// Market the availability of a component such as `playlists` was checked for.
+    pub fn component_market(&self, stem: &str) -> Option<&str> {
+        self.markets.get(stem).map(String::as_str)
+    }
+
+    /// Records the market a component was just exported for, or that it was
+    /// exported without one.
+    pub fn set_component_market(&mut self, stem: &str, market: Option<&str>) {
+        match market {
+            Some(market) => self.markets.insert(stem.to_string(), market.to_string()),
+            None => self.markets.remove(stem),
+        };
+    }
+}
@@ -1,2 +1,6 @@
 pub mod playlists;
+pub mod saved_albums;
+pub mod saved_audiobooks;
+pub mod saved_episodes;
+pub mod saved_shows;
 pub mod saved_tracks;
\ No newline at end of file
@@ -1,12 +1,24 @@
 use anyhow::Result;
 use serde_json::Value;
 
+use crate::select::PlaylistFilter;
 use crate::utils;
 
-pub async fn purge_playlists(access_token: &str, force: bool) -> Result<Vec<String>> {
+pub async fn purge_playlists(access_token: &str, filter: &PlaylistFilter, force: bool) -> Result<Vec<String>> {
     println!("Purging playlists...");
 
     let playlists: Vec<Value> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/playlists").await?;
+    let total = playlists.len();
+    let playlists: Vec<Value> = playlists
+        .into_iter()
+        .filter(|p| filter.matches(p["id"].as_str(), p["name"].as_str().unwrap_or_default(), p["owner"]["id"].as_str()))
+        .collect();
+    if playlists.len() < total {
+        println!(
+            "Skipping {} playlists that do not match the filters.",
+            total - playlists.len()
+        );
+    }
     let playlist_names: Vec<String> = playlists
         .iter()
         .map(|p| p["name"].as_str().unwrap().to_string())
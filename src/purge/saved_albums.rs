@@ -0,0 +1,28 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::utils;
+
+pub async fn purge_saved_albums(access_token: &str, force: bool) -> Result<Vec<String>> {
+    println!("Purging saved albums...");
+
+    let albums: Vec<Value> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/albums").await?;
+    let album_ids: Vec<String> = albums
+        .iter()
+        .filter_map(|a| a["album"]["id"].as_str().map(String::from))
+        .collect();
+
+    if force {
+        for chunk in album_ids.chunks(20) {
+            let url = format!("https://api.spotify.com/v1/me/albums?ids={}", chunk.join(","));
+            utils::delete_spotify(access_token, &url).await?;
+            println!("Purged a chunk of saved albums.");
+        }
+        println!("Saved albums purged successfully.");
+        Ok(Vec::new())
+    } else {
+        println!("Found {} saved albums to purge.", album_ids.len());
+        println!("Saved albums purge dry run complete.");
+        Ok(album_ids)
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::utils;
+
+pub async fn purge_saved_audiobooks(access_token: &str, force: bool) -> Result<Vec<String>> {
+    println!("Purging saved audiobooks...");
+
+    let audiobooks: Vec<Value> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/audiobooks").await?;
+    let audiobook_ids: Vec<String> = audiobooks
+        .iter()
+        .filter_map(|item| {
+            // Items may be bare audiobooks or wrapped with their `added_at`.
+            item["audiobook"]["id"]
+                .as_str()
+                .or(item["id"].as_str())
+                .map(String::from)
+        })
+        .collect();
+
+    if force {
+        for chunk in audiobook_ids.chunks(50) {
+            let url = format!("https://api.spotify.com/v1/me/audiobooks?ids={}", chunk.join(","));
+            utils::delete_spotify(access_token, &url).await?;
+            println!("Purged a chunk of saved audiobooks.");
+        }
+        println!("Saved audiobooks purged successfully.");
+        Ok(Vec::new())
+    } else {
+        println!("Found {} saved audiobooks to purge.", audiobook_ids.len());
+        println!("Saved audiobooks purge dry run complete.");
+        Ok(audiobook_ids)
+    }
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::utils;
+
+pub async fn purge_saved_episodes(access_token: &str, force: bool) -> Result<Vec<String>> {
+    println!("Purging saved episodes...");
+
+    let episodes: Vec<Value> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/episodes").await?;
+    let episode_ids: Vec<String> = episodes
+        .iter()
+        .filter_map(|item| item["episode"]["id"].as_str().map(String::from))
+        .collect();
+
+    if force {
+        for chunk in episode_ids.chunks(50) {
+            let url = format!("https://api.spotify.com/v1/me/episodes?ids={}", chunk.join(","));
+            utils::delete_spotify(access_token, &url).await?;
+            println!("Purged a chunk of saved episodes.");
+        }
+        println!("Saved episodes purged successfully.");
+        Ok(Vec::new())
+    } else {
+        println!("Found {} saved episodes to purge.", episode_ids.len());
+        println!("Saved episodes purge dry run complete.");
+        Ok(episode_ids)
+    }
+}
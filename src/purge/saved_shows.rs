@@ -0,0 +1,28 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::utils;
+
+pub async fn purge_saved_shows(access_token: &str, force: bool) -> Result<Vec<String>> {
+    println!("Purging saved shows...");
+
+    let shows: Vec<Value> = utils::get_all_items(access_token, "https://api.spotify.com/v1/me/shows").await?;
+    let show_ids: Vec<String> = shows
+        .iter()
+        .filter_map(|item| item["show"]["id"].as_str().map(String::from))
+        .collect();
+
+    if force {
+        for chunk in show_ids.chunks(50) {
+            let url = format!("https://api.spotify.com/v1/me/shows?ids={}", chunk.join(","));
+            utils::delete_spotify(access_token, &url).await?;
+            println!("Purged a chunk of saved shows.");
+        }
+        println!("Saved shows purged successfully.");
+        Ok(Vec::new())
+    } else {
+        println!("Found {} saved shows to purge.", show_ids.len());
+        println!("Saved shows purge dry run complete.");
+        Ok(show_ids)
+    }
+}
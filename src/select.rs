@@ -0,0 +1,138 @@
+use regex::{Regex, RegexBuilder};
+use std::str::FromStr;
+
+/// Parts of the library a command can be limited to with `--only`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Component {
+    SavedTracks,
+    Playlists,
+    SavedAlbums,
+    FollowedArtists,
+    SavedShows,
+    SavedEpisodes,
+    SavedAudiobooks,
+    Profile,
+    /// Top tracks and artists and recently played tracks. `export` only, and
+    /// only when named in `--only`, since it needs extra scopes.
+    ListeningHistory,
+}
+
+impl Component {
+    /// Components left out unless `--only` names them.
+    fn is_opt_in(self) -> bool {
+        matches!(self, Component::ListeningHistory)
+    }
+}
+
+/// A playlist name pattern: a case-insensitive glob (`*` and `?`), or a
+/// regular expression when prefixed with `re:`.
+#[derive(Debug, Clone)]
+pub struct NamePattern(Regex);
+
+impl FromStr for NamePattern {
+    type Err = regex::Error;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let expression = match pattern.strip_prefix("re:") {
+            Some(expression) => expression.to_string(),
+            None => glob_to_regex(pattern),
+        };
+        RegexBuilder::new(&expression)
+            .case_insensitive(true)
+            .build()
+            .map(NamePattern)
+    }
+}
+
+impl NamePattern {
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.is_match(name)
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut expression = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            _ => expression.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    expression.push('$');
+    expression
+}
+
+/// Which playlists a command works on. Each kind of include narrows the
+/// selection (a playlist must match one of the values given for it); any
+/// exclude removes a playlist.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct PlaylistFilter {
+    /// Only playlists whose name matches (glob, or regex with a `re:` prefix; repeatable)
+    #[clap(long = "include-playlist", value_name = "PATTERN")]
+    pub include_names: Vec<NamePattern>,
+    /// Skip playlists whose name matches (glob, or regex with a `re:` prefix; repeatable)
+    #[clap(long = "exclude-playlist", value_name = "PATTERN")]
+    pub exclude_names: Vec<NamePattern>,
+    /// Only the playlists with these IDs (repeatable)
+    #[clap(long = "include-playlist-id", value_name = "ID")]
+    pub include_ids: Vec<String>,
+    /// Skip the playlists with these IDs (repeatable)
+    #[clap(long = "exclude-playlist-id", value_name = "ID")]
+    pub exclude_ids: Vec<String>,
+    /// Only playlists owned by these Spotify user IDs (repeatable)
+    #[clap(long = "include-owner", value_name = "USER_ID")]
+    pub include_owners: Vec<String>,
+    /// Skip playlists owned by these Spotify user IDs (repeatable)
+    #[clap(long = "exclude-owner", value_name = "USER_ID")]
+    pub exclude_owners: Vec<String>,
+}
+
+impl PlaylistFilter {
+    /// Checks a playlist. The ID and owner are unknown for dumps written
+    /// before the manifest existed; such playlists never match an ID or owner rule.
+    pub fn matches(&self, id: Option<&str>, name: &str, owner_id: Option<&str>) -> bool {
+        let is_in = |values: &[String], value: Option<&str>| {
+            value.is_some_and(|value| values.iter().any(|candidate| candidate == value))
+        };
+
+        let included = (self.include_names.is_empty()
+            || self.include_names.iter().any(|pattern| pattern.matches(name)))
+            && (self.include_ids.is_empty() || is_in(&self.include_ids, id))
+            && (self.include_owners.is_empty() || is_in(&self.include_owners, owner_id));
+        let excluded = self.exclude_names.iter().any(|pattern| pattern.matches(name))
+            || is_in(&self.exclude_ids, id)
+            || is_in(&self.exclude_owners, owner_id);
+
+        included && !excluded
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include_names.is_empty()
+            && self.exclude_names.is_empty()
+            && self.include_ids.is_empty()
+            && self.exclude_ids.is_empty()
+            && self.include_owners.is_empty()
+            && self.exclude_owners.is_empty()
+    }
+}
+
+/// The `--only` option and playlist filters shared by `export`, `import` and `purge`.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Selection {
+    /// Only work on these parts of the library (comma separated; default: all)
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub only: Vec<Component>,
+    #[clap(flatten)]
+    pub playlists: PlaylistFilter,
+}
+
+impl Selection {
+    pub fn includes(&self, component: Component) -> bool {
+        if self.only.is_empty() {
+            !component.is_opt_in()
+        } else {
+            self.only.contains(&component)
+        }
+    }
+}
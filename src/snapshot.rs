@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File in the dump folder holding the name of the most recent snapshot.
+/// A plain file rather than a symlink, so it works the same on Windows.
+pub const LATEST_FILE: &str = "latest";
+
+// No colons, which Windows does not allow in file names.
+const NAME_FORMAT: &str = "%Y-%m-%dT%H-%M-%SZ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub name: String,
+    pub taken_at: DateTime<Utc>,
+}
+
+/// How many snapshots `prune` keeps. Each rule keeps the newest snapshot of
+/// each of the last N days, ISO weeks or months that have one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Retention {
+    pub daily: usize,
+    pub weekly: usize,
+    pub monthly: usize,
+}
+
+impl Retention {
+    pub fn is_empty(&self) -> bool {
+        self.daily == 0 && self.weekly == 0 && self.monthly == 0
+    }
+}
+
+/// Path of a new snapshot for an export starting now. The directory is not created.
+pub fn new_snapshot_dir(root: &Path) -> PathBuf {
+    root.join(Utc::now().format(NAME_FORMAT).to_string())
+}
+
+pub fn mark_latest(root: &Path, snapshot_dir: &Path) -> Result<()> {
+    let name = snapshot_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("Snapshot directory has no name")?;
+    fs::write(root.join(LATEST_FILE), format!("{}\n", name))
+        .with_context(|| format!("Failed to update {}", root.join(LATEST_FILE).display()))
+}
+
+/// Follows the `latest` pointer of a snapshot folder. Folders without one are returned as is.
+pub fn resolve(root: &Path) -> Result<PathBuf> {
+    let pointer = root.join(LATEST_FILE);
+    if !pointer.is_file() {
+        return Ok(root.to_path_buf());
+    }
+
+    let name = fs::read_to_string(&pointer)
+        .with_context(|| format!("Failed to read {}", pointer.display()))?;
+    let snapshot_dir = root.join(name.trim());
+    if !snapshot_dir.is_dir() {
+        return Err(anyhow::anyhow!(
+            "{} points to a missing snapshot: {}",
+            pointer.display(),
+            name.trim()
+        ));
+    }
+    Ok(snapshot_dir)
+}
+
+/// Lists the snapshots in a dump folder, newest first. Other entries are ignored.
+pub fn list(root: &Path) -> Result<Vec<Snapshot>> {
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(root).with_context(|| format!("Failed to read {}", root.display()))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let Some(name) = entry.file_name().to_str().map(String::from) else {
+            continue;
+        };
+        if let Ok(taken_at) = NaiveDateTime::parse_from_str(&name, NAME_FORMAT) {
+            snapshots.push(Snapshot {
+                name,
+                taken_at: taken_at.and_utc(),
+            });
+        }
+    }
+
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.taken_at));
+    Ok(snapshots)
+}
+
+/// Maps a time to the retention period (day, week or month) it falls in.
+type Period = fn(&DateTime<Utc>) -> (i32, u32);
+
+/// Picks the snapshots to keep. `snapshots` must be sorted newest first; the
+/// newest one is always kept.
+pub fn select_kept(snapshots: &[Snapshot], retention: Retention) -> HashSet<String> {
+    let mut kept = HashSet::new();
+    if let Some(newest) = snapshots.first() {
+        kept.insert(newest.name.clone());
+    }
+
+    let rules: [(usize, Period); 3] = [
+        (retention.daily, |time| (time.year(), time.ordinal())),
+        (retention.weekly, |time| {
+            let week = time.iso_week();
+            (week.year(), week.week())
+        }),
+        (retention.monthly, |time| (time.year(), time.month())),
+    ];
+
+    for (count, period) in rules {
+        let mut periods = HashSet::new();
+        for snapshot in snapshots {
+            if periods.len() == count {
+                break;
+            }
+            // Newest first, so the first snapshot seen in a period is its newest.
+            if periods.insert(period(&snapshot.taken_at)) {
+                kept.insert(snapshot.name.clone());
+            }
+        }
+    }
+
+    kept
+}
+
+/// Deletes the snapshots the retention rules do not keep and returns their names.
+/// The snapshot `latest` points to is always kept, even when a newer one was
+/// left behind by a failed export.
+pub fn prune(root: &Path, retention: Retention, force: bool) -> Result<Vec<String>> {
+    let snapshots = list(root)?;
+    let mut kept = select_kept(&snapshots, retention);
+    let latest = resolve(root)?;
+    if latest != root {
+        if let Some(name) = latest.file_name().and_then(|name| name.to_str()) {
+            kept.insert(name.to_string());
+        }
+    }
+    let removed: Vec<String> = snapshots
+        .into_iter()
+        .filter(|snapshot| !kept.contains(&snapshot.name))
+        .map(|snapshot| snapshot.name)
+        .collect();
+
+    for name in &removed {
+        if force {
+            fs::remove_dir_all(root.join(name))
+                .with_context(|| format!("Failed to remove snapshot {}", name))?;
+            println!("Removed snapshot {}", name);
+        } else {
+            println!("Dry run: would have removed snapshot {}", name);
+        }
+    }
+    println!("Kept {} snapshots.", kept.len());
+
+    Ok(removed)
+}
@@ -0,0 +1,454 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Transaction};
+use std::path::Path;
+
+use crate::dump::PlaylistDump;
+use crate::format::{AlbumRecord, ArtistRecord, TrackRecord};
+use crate::manifest::PlaylistEntry;
+use crate::types::Owner;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS exports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    exported_at TEXT NOT NULL,
+    component TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS artists (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS albums (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    release_date TEXT
+);
+CREATE TABLE IF NOT EXISTS tracks (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    uri TEXT,
+    duration_ms INTEGER,
+    explicit INTEGER,
+    isrc TEXT,
+    album_id TEXT REFERENCES albums(id)
+);
+CREATE TABLE IF NOT EXISTS track_artists (
+    track_id TEXT NOT NULL REFERENCES tracks(id),
+    position INTEGER NOT NULL,
+    artist_id TEXT NOT NULL REFERENCES artists(id),
+    PRIMARY KEY (track_id, position)
+);
+CREATE TABLE IF NOT EXISTS playlists (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    public INTEGER,
+    collaborative INTEGER NOT NULL,
+    owner_id TEXT,
+    owner_display_name TEXT,
+    snapshot_id TEXT,
+    followers INTEGER,
+    images TEXT,
+    position INTEGER NOT NULL,
+    export_id INTEGER NOT NULL REFERENCES exports(id)
+);
+CREATE TABLE IF NOT EXISTS playlist_items (
+    export_id INTEGER NOT NULL REFERENCES exports(id),
+    playlist_id TEXT NOT NULL REFERENCES playlists(id),
+    position INTEGER NOT NULL,
+    track_id TEXT NOT NULL REFERENCES tracks(id),
+    added_at TEXT,
+    added_by TEXT,
+    PRIMARY KEY (export_id, playlist_id, position)
+);
+CREATE TABLE IF NOT EXISTS saved_tracks (
+    export_id INTEGER NOT NULL REFERENCES exports(id),
+    track_id TEXT NOT NULL REFERENCES tracks(id),
+    added_at TEXT,
+    PRIMARY KEY (export_id, track_id)
+);
+";
+
+/// Changes to the tables above, applied in order to new databases and to
+/// databases written by earlier versions. `PRAGMA user_version` records how
+/// many have been applied.
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE playlists ADD COLUMN followed INTEGER NOT NULL DEFAULT 0;",
+    // Availability depends on the market of each export, so it is kept per export.
+    "ALTER TABLE saved_tracks ADD COLUMN is_playable INTEGER;
+     ALTER TABLE saved_tracks ADD COLUMN restriction TEXT;
+     ALTER TABLE playlist_items ADD COLUMN is_playable INTEGER;
+     ALTER TABLE playlist_items ADD COLUMN restriction TEXT;",
+];
+
+/// Columns a read-only database of an older version cannot be migrated to
+/// have; they are read as NULL instead.
+const AVAILABILITY_VERSION: usize = 2;
+
+/// A playlist item as read: track ID, added at, added by, position, whether
+/// it was playable and why not.
+type PlaylistItemRow = (String, Option<String>, Option<String>, u32, Option<bool>, Option<String>);
+
+/// An SQLite database holding the library across exports.
+///
+/// Tracks, artists, albums and playlists are upserted, so they always hold the
+/// latest metadata. Saved tracks and playlist items are keyed by the export
+/// they were seen in, so every export adds a snapshot instead of replacing
+/// the previous one.
+pub struct Library {
+    conn: Connection,
+}
+
+impl Library {
+    /// Opens the database, creating it and its tables if needed, and brings
+    /// the tables of older databases up to date.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut conn = Connection::open(path)
+            .with_context(|| format!("Failed to open SQLite database: {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .context("Failed to create SQLite tables")?;
+
+        let applied: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if applied > MIGRATIONS.len() {
+            return Err(anyhow::anyhow!(
+                "{} was written by a newer version of spoti-dump; please upgrade.",
+                path.display()
+            ));
+        }
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)
+                .context("Failed to update the SQLite tables")?;
+            tx.pragma_update(None, "user_version", version + 1)?;
+            tx.commit()?;
+        }
+
+        Ok(Library { conn })
+    }
+
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open SQLite database: {}", path.display()))?;
+        Ok(Library { conn })
+    }
+
+    pub fn write_saved_tracks(&mut self, tracks: &[TrackRecord]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let export_id = insert_export(&tx, "saved_tracks")?;
+
+        for track in tracks {
+            upsert_track(&tx, track)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO saved_tracks (export_id, track_id, added_at, is_playable, restriction)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![export_id, track.id, track.added_at, track.is_playable, track.restriction],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn write_playlists(&mut self, playlists: &[PlaylistDump]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let export_id = insert_export(&tx, "playlists")?;
+
+        for (position, playlist) in playlists.iter().enumerate() {
+            let Some(entry) = &playlist.entry else {
+                continue;
+            };
+            tx.execute(
+                "INSERT INTO playlists (id, name, description, public, collaborative, owner_id,
+                    owner_display_name, snapshot_id, followers, images, position, export_id, followed)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+                 ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    description = excluded.description,
+                    public = excluded.public,
+                    collaborative = excluded.collaborative,
+                    owner_id = excluded.owner_id,
+                    owner_display_name = excluded.owner_display_name,
+                    snapshot_id = excluded.snapshot_id,
+                    followers = excluded.followers,
+                    images = excluded.images,
+                    position = excluded.position,
+                    export_id = excluded.export_id,
+                    followed = excluded.followed",
+                params![
+                    entry.id,
+                    entry.name,
+                    entry.description,
+                    entry.public,
+                    entry.collaborative,
+                    entry.owner.id,
+                    entry.owner.display_name,
+                    entry.snapshot_id,
+                    entry.followers,
+                    serde_json::to_string(&entry.images)?,
+                    position as i64,
+                    export_id,
+                    entry.followed,
+                ],
+            )?;
+
+            for (item_position, track) in playlist.tracks.iter().enumerate() {
+                // Local files have no track ID to refer to; their position is left out.
+                if track.is_local {
+                    continue;
+                }
+                upsert_track(&tx, track)?;
+                let position = track.position.map_or(item_position as i64, i64::from);
+                tx.execute(
+                    "INSERT INTO playlist_items (export_id, playlist_id, position, track_id, added_at, added_by,
+                        is_playable, restriction)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        export_id,
+                        entry.id,
+                        position,
+                        track.id,
+                        track.added_at,
+                        track.added_by,
+                        track.is_playable,
+                        track.restriction,
+                    ],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Reads the saved tracks of the most recent export.
+    pub fn read_saved_tracks(&self) -> Result<Vec<TrackRecord>> {
+        let Some(export_id) = self.latest_export("saved_tracks")? else {
+            return Ok(Vec::new());
+        };
+
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT track_id, added_at, {} FROM saved_tracks WHERE export_id = ?1 ORDER BY rowid",
+            self.availability_columns()?
+        ))?;
+        let rows = statement
+            .query_map(params![export_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(String, Option<String>, Option<bool>, Option<String>)>>>()?;
+
+        rows.into_iter()
+            .map(|(track_id, added_at, is_playable, restriction)| {
+                let mut track = self.read_track(&track_id, added_at)?;
+                track.is_playable = is_playable;
+                track.restriction = restriction;
+                Ok(track)
+            })
+            .collect()
+    }
+
+    /// Reads the playlists of the most recent export, in their original order.
+    pub fn read_playlists(&self) -> Result<Vec<PlaylistDump>> {
+        let Some(export_id) = self.latest_export("playlists")? else {
+            return Ok(Vec::new());
+        };
+
+        // Read-only databases cannot be migrated; older ones have no `followed` column.
+        let version: usize = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let followed = if version >= 1 { "followed" } else { "0" };
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT id, name, description, public, collaborative, owner_id, owner_display_name,
+                snapshot_id, followers, images, {}
+             FROM playlists WHERE export_id = ?1 ORDER BY position",
+            followed
+        ))?;
+        let entries = statement
+            .query_map(params![export_id], |row| {
+                let images: Option<String> = row.get(9)?;
+                Ok(PlaylistEntry {
+                    // Playlists in a database are not backed by files.
+                    file: String::new(),
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    public: row.get(3)?,
+                    collaborative: row.get(4)?,
+                    owner: Owner {
+                        id: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                        display_name: row.get(6)?,
+                    },
+                    snapshot_id: row.get(7)?,
+                    followers: row.get(8)?,
+                    followed: row.get(10)?,
+                    cover: None,
+                    track_count: None,
+                    contributors: Vec::new(),
+                    images: images
+                        .and_then(|images| serde_json::from_str(&images).ok())
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut items = self.conn.prepare(&format!(
+            "SELECT track_id, added_at, added_by, position, {} FROM playlist_items
+             WHERE export_id = ?1 AND playlist_id = ?2 ORDER BY position",
+            self.availability_columns()?
+        ))?;
+        let mut playlists = Vec::new();
+        for entry in entries {
+            let rows = items
+                .query_map(params![export_id, entry.id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+                })?
+                .collect::<rusqlite::Result<Vec<PlaylistItemRow>>>()?;
+            let tracks = rows
+                .into_iter()
+                .map(|(track_id, added_at, added_by, position, is_playable, restriction)| {
+                    let mut track = self.read_track(&track_id, added_at)?;
+                    track.added_by = added_by;
+                    track.position = Some(position);
+                    track.is_playable = is_playable;
+                    track.restriction = restriction;
+                    Ok(track)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            playlists.push(PlaylistDump {
+                name: entry.name.clone(),
+                entry: Some(entry),
+                tracks,
+            });
+        }
+
+        Ok(playlists)
+    }
+
+    fn availability_columns(&self) -> Result<&'static str> {
+        let version: usize = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        Ok(if version >= AVAILABILITY_VERSION {
+            "is_playable, restriction"
+        } else {
+            "NULL, NULL"
+        })
+    }
+
+    fn latest_export(&self, component: &str) -> Result<Option<i64>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT MAX(id) FROM exports WHERE component = ?1",
+                params![component],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten())
+    }
+
+    fn read_track(&self, track_id: &str, added_at: Option<String>) -> Result<TrackRecord> {
+        let mut track = self.conn.query_row(
+            "SELECT t.name, t.uri, t.duration_ms, t.explicit, t.isrc, a.id, a.name, a.release_date
+             FROM tracks t LEFT JOIN albums a ON a.id = t.album_id
+             WHERE t.id = ?1",
+            params![track_id],
+            |row| {
+                Ok(TrackRecord {
+                    added_at,
+                    id: track_id.to_string(),
+                    name: row.get(0)?,
+                    artists: Vec::new(),
+                    album: AlbumRecord {
+                        id: row.get(5)?,
+                        name: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                        release_date: row.get(7)?,
+                    },
+                    uri: row.get(1)?,
+                    duration_ms: row.get(2)?,
+                    explicit: row.get(3)?,
+                    isrc: row.get(4)?,
+                    added_by: None,
+                    position: None,
+                    is_local: false,
+                    is_playable: None,
+                    restriction: None,
+                })
+            },
+        )?;
+
+        let mut statement = self.conn.prepare_cached(
+            "SELECT ar.id, ar.name FROM track_artists ta JOIN artists ar ON ar.id = ta.artist_id
+             WHERE ta.track_id = ?1 ORDER BY ta.position",
+        )?;
+        track.artists = statement
+            .query_map(params![track_id], |row| {
+                Ok(ArtistRecord {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(track)
+    }
+}
+
+fn insert_export(tx: &Transaction, component: &str) -> Result<i64> {
+    tx.execute(
+        "INSERT INTO exports (exported_at, component) VALUES (?1, ?2)",
+        params![Utc::now().to_rfc3339(), component],
+    )?;
+    Ok(tx.last_insert_rowid())
+}
+
+fn upsert_track(tx: &Transaction, track: &TrackRecord) -> Result<()> {
+    if let Some(album_id) = &track.album.id {
+        tx.execute(
+            "INSERT INTO albums (id, name, release_date) VALUES (?1, ?2, ?3)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, release_date = excluded.release_date",
+            params![album_id, track.album.name, track.album.release_date],
+        )?;
+    }
+
+    tx.execute(
+        "INSERT INTO tracks (id, name, uri, duration_ms, explicit, isrc, album_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+            name = COALESCE(NULLIF(excluded.name, ''), name),
+            uri = COALESCE(excluded.uri, uri),
+            duration_ms = COALESCE(excluded.duration_ms, duration_ms),
+            explicit = COALESCE(excluded.explicit, explicit),
+            isrc = COALESCE(excluded.isrc, isrc),
+            album_id = COALESCE(excluded.album_id, album_id)",
+        params![
+            track.id,
+            track.name,
+            track.uri,
+            track.duration_ms,
+            track.explicit,
+            track.isrc,
+            track.album.id,
+        ],
+    )?;
+
+    // Artists without an ID (e.g. read back from a CSV dump) cannot be
+    // normalized; keep the links stored by an earlier export instead.
+    if track.artists.iter().all(|artist| artist.id.is_none()) {
+        return Ok(());
+    }
+    tx.execute("DELETE FROM track_artists WHERE track_id = ?1", params![track.id])?;
+    for (position, artist) in track.artists.iter().enumerate() {
+        let Some(artist_id) = &artist.id else {
+            continue;
+        };
+        tx.execute(
+            "INSERT INTO artists (id, name) VALUES (?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name",
+            params![artist_id, artist.name],
+        )?;
+        tx.execute(
+            "INSERT INTO track_artists (track_id, position, artist_id) VALUES (?1, ?2, ?3)",
+            params![track.id, position as i64, artist_id],
+        )?;
+    }
+
+    Ok(())
+}
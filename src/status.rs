@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use crate::diff::{self, DumpDiff, TrackChange};
+use crate::dump::Source;
+use crate::export;
+use crate::select::PlaylistFilter;
+use crate::utils;
+
+/// Compares the live account with a dump. Nothing is modified on either side;
+/// the differences are reported from the dump to the account.
+pub async fn status(access_token: &str, source: &Source) -> Result<DumpDiff> {
+    let dump_saved_tracks = source.read_saved_tracks()?;
+    let dump_playlists = source.read_playlists()?;
+
+    println!("Fetching the current library...");
+    let (live_saved_tracks, _) = export::saved_tracks::fetch_saved_tracks(access_token, None).await?;
+    let user_id = utils::get_user_id(access_token).await?;
+    let live_playlists =
+        export::playlists::fetch_playlists(access_token, &user_id, &PlaylistFilter::default()).await?;
+
+    Ok(diff::diff(
+        &dump_saved_tracks,
+        &dump_playlists,
+        &live_saved_tracks,
+        &live_playlists,
+    ))
+}
+
+pub fn print_status(differences: &DumpDiff) {
+    if differences.is_empty() {
+        println!("The account matches the dump.");
+        return;
+    }
+
+    let saved_tracks = &differences.saved_tracks;
+    print_tracks("Saved tracks only in the dump", &saved_tracks.removed);
+    print_tracks("Saved tracks only on the account", &saved_tracks.added);
+    if !saved_tracks.moved.is_empty() {
+        println!("{} saved tracks are in a different order.", saved_tracks.moved.len());
+    }
+
+    if !differences.playlists_removed.is_empty() {
+        println!("Playlists only in the dump ({}):", differences.playlists_removed.len());
+        for playlist in &differences.playlists_removed {
+            println!("  '{}' ({} tracks)", playlist.name, playlist.tracks);
+        }
+    }
+    if !differences.playlists_added.is_empty() {
+        println!("Playlists only on the account ({}):", differences.playlists_added.len());
+        for playlist in &differences.playlists_added {
+            println!("  '{}' ({} tracks)", playlist.name, playlist.tracks);
+        }
+    }
+
+    for playlist in &differences.playlists_changed {
+        println!("Playlist '{}':", playlist.name);
+        if let Some(dump_name) = &playlist.renamed_from {
+            println!("  named '{}' in the dump", dump_name);
+        }
+        for track in &playlist.tracks.removed {
+            println!("  only in the dump: {}", track.describe());
+        }
+        for track in &playlist.tracks.added {
+            println!("  only on the account: {}", track.describe());
+        }
+        if !playlist.tracks.moved.is_empty() {
+            println!("  {} tracks are in a different order", playlist.tracks.moved.len());
+        }
+    }
+}
+
+fn print_tracks(title: &str, tracks: &[TrackChange]) {
+    if tracks.is_empty() {
+        return;
+    }
+    println!("{} ({}):", title, tracks.len());
+    for track in tracks {
+        println!("  {}", track.describe());
+    }
+}
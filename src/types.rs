@@ -1,26 +1,171 @@
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Deserialize)]
 pub struct Track {
     pub id: Option<String>,
     #[serde(default, deserialize_with = "default_on_null")]
     pub name: String,
+    pub uri: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub explicit: Option<bool>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub external_ids: ExternalIds,
     #[serde(default, deserialize_with = "default_on_null")]
     pub artists: Vec<Artist>,
     #[serde(default, deserialize_with = "default_on_null")]
     pub album: Album,
+    /// Only present when a `market` was requested.
+    pub is_playable: Option<bool>,
+    pub restrictions: Option<Restrictions>,
+    /// The track that was asked for, when Spotify substituted a version
+    /// playable in the requested market.
+    pub linked_from: Option<LinkedFrom>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Restrictions {
+    /// `market`, `product`, `explicit`, ...
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkedFrom {
+    pub id: Option<String>,
+    pub uri: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 pub struct Artist {
+    pub id: Option<String>,
     #[serde(default, deserialize_with = "default_on_null")]
     pub name: String,
 }
 
+/// A full artist object, as listed by `/me/following`.
+#[derive(Debug, Deserialize)]
+pub struct FullArtist {
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub name: String,
+    pub uri: Option<String>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub genres: Vec<String>,
+    pub popularity: Option<u32>,
+    pub followers: Option<Followers>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Followers {
+    pub total: u32,
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub struct Album {
+    pub id: Option<String>,
     #[serde(default, deserialize_with = "default_on_null")]
     pub name: String,
+    pub release_date: Option<String>,
+    pub uri: Option<String>,
+    pub album_type: Option<String>,
+    pub total_tracks: Option<u32>,
+    /// Only in full album objects, e.g. from `/me/albums`.
+    pub label: Option<String>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub artists: Vec<Artist>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub external_ids: ExternalIds,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct Show {
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub name: String,
+    pub publisher: Option<String>,
+    pub total_episodes: Option<u32>,
+    pub uri: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Episode {
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub name: String,
+    pub release_date: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub uri: Option<String>,
+    /// Only present when the token has the `user-read-playback-position` scope.
+    pub resume_point: Option<ResumePoint>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub show: Show,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResumePoint {
+    pub fully_played: Option<bool>,
+    pub resume_position_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Audiobook {
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub name: String,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub authors: Vec<Person>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub narrators: Vec<Person>,
+    pub publisher: Option<String>,
+    pub total_chapters: Option<u32>,
+    pub uri: Option<String>,
+}
+
+/// An author or narrator; Spotify only gives their name.
+#[derive(Debug, Deserialize)]
+pub struct Person {
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ExternalIds {
+    pub isrc: Option<String>,
+    pub upc: Option<String>,
+}
+
+/// The current user's profile, from `/me`. `country`, `product` and
+/// `explicit_content` need the `user-read-private` scope.
+#[derive(Debug, Deserialize)]
+pub struct User {
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub id: String,
+    pub display_name: Option<String>,
+    pub country: Option<String>,
+    pub product: Option<String>,
+    pub followers: Option<Followers>,
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub images: Vec<Image>,
+    pub explicit_content: Option<ExplicitContent>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExplicitContent {
+    pub filter_enabled: bool,
+    pub filter_locked: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Owner {
+    #[serde(default, deserialize_with = "default_on_null")]
+    pub id: String,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Image {
+    pub url: String,
+    pub height: Option<u32>,
+    pub width: Option<u32>,
 }
 
 fn default_on_null<'de, D, T>(deserializer: D) -> Result<T, D::Error>
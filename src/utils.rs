@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use url::Url;
 
 pub async fn get_user_id(access_token: &str) -> Result<String> {
     let client = reqwest::Client::new();
@@ -28,6 +29,35 @@ pub async fn get_user_id(access_token: &str) -> Result<String> {
         .map(String::from)
 }
 
+pub async fn get_json<T: DeserializeOwned>(access_token: &str, url: &str) -> Result<T> {
+    let client = reqwest::Client::new();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+
+    let response = client.get(url).headers(headers).send().await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to get {} from Spotify", url));
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Adds the `market` query parameter, which makes Spotify report whether
+/// each track is playable there (`from_token` means the account's country).
+pub fn with_market(url: &str, market: Option<&str>) -> String {
+    match market {
+        Some(market) => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!("{}{}market={}", url, separator, market)
+        }
+        None => url.to_string(),
+    }
+}
+
 pub async fn get_all_items<T: DeserializeOwned>(access_token: &str, url: &str) -> Result<Vec<T>> {
     let client = reqwest::Client::new();
     let mut headers = HeaderMap::new();
@@ -56,6 +86,94 @@ pub async fn get_all_items<T: DeserializeOwned>(access_token: &str, url: &str) -
     Ok(items)
 }
 
+/// Pages through a cursor-based collection such as `/me/following`. These
+/// wrap the page in an object named after the item type (`key`) and carry
+/// an `after` cursor instead of offsets.
+pub async fn get_all_cursor_items<T: DeserializeOwned>(
+    access_token: &str,
+    url: &str,
+    key: &str,
+) -> Result<Vec<T>> {
+    let client = reqwest::Client::new();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+
+    let base_url = Url::parse(url)?;
+    let mut items = Vec::new();
+    let mut after: Option<String> = None;
+
+    loop {
+        let mut page_url = base_url.clone();
+        if let Some(after) = &after {
+            page_url.query_pairs_mut().append_pair("after", after);
+        }
+
+        let response = client.get(page_url).headers(headers.clone()).send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Failed to get items from Spotify"));
+        }
+
+        let mut data: Value = response.json().await?;
+        let page = &mut data[key];
+        let new_items: Vec<T> = serde_json::from_value(page["items"].take())?;
+        let last_page = new_items.is_empty() || page["next"].is_null();
+        items.extend(new_items);
+
+        match page["cursors"]["after"].as_str() {
+            Some(cursor) if !last_page => after = Some(cursor.to_string()),
+            _ => break,
+        }
+    }
+
+    Ok(items)
+}
+
+/// Pages through a collection like `get_all_items`, but stops at the first
+/// item for which `stop` returns true (which is not included). Also returns
+/// the `total` of the collection as reported by the first page.
+pub async fn get_items_until<T, F>(access_token: &str, url: &str, mut stop: F) -> Result<(Vec<T>, u32)>
+where
+    T: DeserializeOwned,
+    F: FnMut(&T) -> bool,
+{
+    let client = reqwest::Client::new();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", access_token))?,
+    );
+
+    let mut items = Vec::new();
+    let mut total = None;
+    let mut next_url = Some(url.to_string());
+
+    while let Some(url) = next_url {
+        let response = client.get(&url).headers(headers.clone()).send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Failed to get items from Spotify"));
+        }
+
+        let mut data: Value = response.json().await?;
+        total = total.or(data["total"].as_u64().map(|total| total as u32));
+        let new_items: Vec<T> = serde_json::from_value(data["items"].take())?;
+        for item in new_items {
+            if stop(&item) {
+                return Ok((items, total.unwrap_or_default()));
+            }
+            items.push(item);
+        }
+
+        next_url = data["next"].as_str().map(|s| s.to_string());
+    }
+
+    Ok((items, total.unwrap_or_default()))
+}
+
 pub async fn delete_spotify(access_token: &str, url: &str) -> Result<()> {
     let client = reqwest::Client::new();
     let mut headers = HeaderMap::new();
@@ -0,0 +1,70 @@
+use spoti_dump::archive;
+use spoti_dump::dump::{DumpDir, Source};
+use spoti_dump::manifest::Manifest;
+use std::fs;
+use std::path::Path;
+
+fn write_dump(dump_dir: &Path) {
+    fs::write(
+        dump_dir.join("saved_tracks.csv"),
+        "Added At,Track Name,Artists,Album,Id\nUnknown,Song,Artist,Album,abc123\n",
+    )
+    .unwrap();
+    Manifest::default().save(dump_dir).unwrap();
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let dump_dir = dir.path().join("dump");
+    fs::create_dir(&dump_dir).unwrap();
+    write_dump(&dump_dir);
+
+    for name in ["backup.tar.zst", "backup.zip"] {
+        let archive_path = dir.path().join(name);
+        archive::create(&dump_dir, &archive_path).unwrap();
+
+        let verification = archive::verify(&archive_path).unwrap();
+        assert!(verification.is_ok());
+        assert_eq!(verification.verified, 1);
+
+        let opened = DumpDir::open(&archive_path).unwrap();
+        let source = Source::Dir {
+            path: opened.path().to_path_buf(),
+            format: None,
+        };
+        assert_eq!(source.read_saved_tracks().unwrap()[0].id, "abc123");
+    }
+}
+
+#[test]
+fn test_archive_detects_tampering() {
+    let dir = tempfile::tempdir().unwrap();
+    write_dump(dir.path());
+    archive::create(dir.path(), &dir.path().join("backup.zip")).unwrap();
+
+    // Re-archive a modified copy without updating the recorded checksums.
+    let tampered = tempfile::tempdir().unwrap();
+    archive::extract(&dir.path().join("backup.zip"), tampered.path()).unwrap();
+    fs::write(tampered.path().join("saved_tracks.csv"), "Added At,Track Name,Artists,Album,Id\n").unwrap();
+    let manifest = fs::read(tampered.path().join("manifest.json")).unwrap();
+
+    let repacked = tempfile::tempdir().unwrap();
+    let archive_path = repacked.path().join("tampered.zip");
+    {
+        use std::io::Write;
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("manifest.json", options).unwrap();
+        writer.write_all(&manifest).unwrap();
+        writer.start_file("saved_tracks.csv", options).unwrap();
+        writer
+            .write_all(&fs::read(tampered.path().join("saved_tracks.csv")).unwrap())
+            .unwrap();
+        writer.finish().unwrap();
+    }
+
+    let verification = archive::verify(&archive_path).unwrap();
+    assert_eq!(verification.mismatched, ["saved_tracks.csv"]);
+    assert!(DumpDir::open(&archive_path).is_err());
+}
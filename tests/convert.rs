@@ -0,0 +1,58 @@
+use spoti_dump::convert::{self, PlaylistFormat};
+use spoti_dump::dump::{PlaylistDump, Source};
+use spoti_dump::format::{self, AlbumRecord, ArtistRecord, Format, TrackRecord};
+use std::fs;
+
+fn playlist_dump() -> PlaylistDump {
+    PlaylistDump {
+        name: "Rock & Roll".to_string(),
+        entry: None,
+        tracks: vec![TrackRecord {
+            id: "4uLU6hMCjMI75M1A2tKUQC".to_string(),
+            name: "Never Gonna Give You Up".to_string(),
+            artists: vec![ArtistRecord {
+                id: None,
+                name: "Rick Astley".to_string(),
+            }],
+            album: AlbumRecord {
+                id: None,
+                name: "Whenever You Need Somebody".to_string(),
+                release_date: None,
+            },
+            duration_ms: Some(213573),
+            ..Default::default()
+        }],
+    }
+}
+
+#[test]
+fn test_m3u8_entries() {
+    let m3u8 = convert::to_m3u8(&playlist_dump());
+    assert!(m3u8.starts_with("#EXTM3U\n"));
+    assert!(m3u8.contains("#EXTINF:213,Rick Astley - Never Gonna Give You Up\n"));
+    assert!(m3u8.contains("\nspotify:track:4uLU6hMCjMI75M1A2tKUQC\n"));
+}
+
+#[test]
+fn test_xspf_escapes_metadata() {
+    let xspf = convert::to_xspf(&playlist_dump());
+    assert!(xspf.contains("<title>Rock &amp; Roll</title>"));
+    assert!(xspf.contains("<location>spotify:track:4uLU6hMCjMI75M1A2tKUQC</location>"));
+    assert!(xspf.contains("<duration>213573</duration>"));
+}
+
+#[test]
+fn test_convert_legacy_dump() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let playlist = playlist_dump();
+    format::write_tracks(&dump_dir.path().join("Rock.csv"), Format::Csv, &playlist.tracks).unwrap();
+
+    let source = Source::Dir {
+        path: dump_dir.path().to_path_buf(),
+        format: None,
+    };
+    convert::convert_dump(&source, dump_dir.path(), &[PlaylistFormat::M3u8, PlaylistFormat::Xspf], true).unwrap();
+
+    assert!(fs::read_to_string(dump_dir.path().join("m3u8/Rock.m3u8")).unwrap().contains("#EXTINF:213,"));
+    assert!(dump_dir.path().join("xspf/Rock.xspf").exists());
+}
@@ -0,0 +1,55 @@
+use base64::{engine::general_purpose, Engine as _};
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, RgbImage};
+use spoti_dump::cover::{custom_cover, encode_for_upload, MAX_UPLOAD_SIZE};
+use spoti_dump::types::Image;
+
+fn image(url: &str) -> Image {
+    Image {
+        url: url.to_string(),
+        height: Some(640),
+        width: Some(640),
+    }
+}
+
+#[test]
+fn test_generated_mosaics_are_not_custom_covers() {
+    let mosaic = [image("https://mosaic.scdn.co/640/ab67616d0000b273")];
+    assert!(custom_cover(&mosaic).is_none());
+
+    let custom = [image("https://image-cdn-ak.spotifycdn.com/image/ab67706c0000da84")];
+    assert_eq!(custom_cover(&custom).unwrap().url, custom[0].url);
+    assert!(custom_cover(&[]).is_none());
+}
+
+#[test]
+fn test_large_covers_are_shrunk_below_the_upload_limit() {
+    // Noise compresses badly, so this is well over the limit at full quality.
+    let mut seed: u32 = 1;
+    let noise = RgbImage::from_fn(1200, 1200, |_, _| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        let [r, g, b, _] = seed.to_le_bytes();
+        image::Rgb([r, g, b])
+    });
+    let mut jpeg = Vec::new();
+    DynamicImage::ImageRgb8(noise)
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, 100))
+        .unwrap();
+    assert!(jpeg.len() > MAX_UPLOAD_SIZE);
+
+    let encoded = encode_for_upload(&jpeg).unwrap();
+    assert!(encoded.len() <= MAX_UPLOAD_SIZE);
+    let decoded = general_purpose::STANDARD.decode(&encoded).unwrap();
+    assert!(image::load_from_memory(&decoded).is_ok());
+}
+
+#[test]
+fn test_small_jpeg_covers_are_uploaded_as_is() {
+    let mut jpeg = Vec::new();
+    DynamicImage::ImageRgb8(RgbImage::new(64, 64))
+        .write_with_encoder(JpegEncoder::new(&mut jpeg))
+        .unwrap();
+
+    let encoded = encode_for_upload(&jpeg).unwrap();
+    assert_eq!(general_purpose::STANDARD.decode(encoded).unwrap(), jpeg);
+}
@@ -0,0 +1,68 @@
+use spoti_dump::archive;
+use spoti_dump::crypto;
+use spoti_dump::dump::DumpDir;
+use spoti_dump::manifest::Manifest;
+use std::fs;
+
+#[test]
+fn test_encrypt_round_trip() {
+    let encrypted = crypto::encrypt(b"listening history", "correct horse").unwrap();
+    assert!(crypto::is_encrypted_data(&encrypted));
+    assert_eq!(crypto::decrypt(&encrypted, "correct horse").unwrap(), b"listening history");
+}
+
+#[test]
+fn test_wrong_passphrase_and_tampering_fail() {
+    let mut encrypted = crypto::encrypt(b"listening history", "correct horse").unwrap();
+    assert!(crypto::decrypt(&encrypted, "battery staple").is_err());
+
+    let last = encrypted.len() - 1;
+    encrypted[last] ^= 1;
+    assert!(crypto::decrypt(&encrypted, "correct horse").is_err());
+}
+
+#[test]
+fn test_encrypted_archive_opens_transparently() {
+    std::env::set_var(crypto::PASSPHRASE_ENV, "correct horse");
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("saved_tracks.csv"),
+        "Added At,Track Name,Artists,Album,Id\nUnknown,Song,Artist,Album,abc123\n",
+    )
+    .unwrap();
+    Manifest::default().save(dir.path()).unwrap();
+
+    let output = tempfile::tempdir().unwrap();
+    let archive_path = output.path().join("backup.tar.zst");
+    archive::create(dir.path(), &archive_path).unwrap();
+    let encrypted_path = crypto::encrypt_file(&archive_path, "correct horse").unwrap();
+    assert!(!archive_path.exists());
+
+    assert!(archive::verify(&encrypted_path).unwrap().is_ok());
+    let opened = DumpDir::open(&encrypted_path).unwrap();
+    assert!(opened.path().join("saved_tracks.csv").exists());
+}
+
+#[test]
+fn test_sealed_dump_leaves_no_plaintext() {
+    std::env::set_var(crypto::PASSPHRASE_ENV, "correct horse");
+    let staging = tempfile::tempdir().unwrap();
+    let staged = staging.path().to_path_buf();
+    fs::write(staged.join("saved_tracks.csv"), "Added At,Track Name,Artists,Album,Id\n").unwrap();
+    fs::create_dir(staged.join("listening_history")).unwrap();
+    fs::write(staged.join("listening_history").join("recently_played.csv"), "Played At\n").unwrap();
+    Manifest::default().save(&staged).unwrap();
+
+    let output = tempfile::tempdir().unwrap();
+    let archive_path = output.path().join("backup.zip");
+    let encrypted_path = crypto::seal_dump(staging, &archive_path, "correct horse").unwrap();
+
+    assert!(!staged.exists());
+    let left: Vec<_> = fs::read_dir(output.path()).unwrap().map(|entry| entry.unwrap().path()).collect();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0], encrypted_path);
+    assert!(crypto::is_encrypted(&encrypted_path).unwrap());
+
+    let opened = DumpDir::open(&encrypted_path).unwrap();
+    assert!(opened.path().join("listening_history").join("recently_played.csv").exists());
+}
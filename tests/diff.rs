@@ -0,0 +1,104 @@
+use spoti_dump::diff::{diff, diff_tracks};
+use spoti_dump::dump::{PlaylistDump, Source};
+use spoti_dump::format::{self, ArtistRecord, Format, TrackRecord};
+use spoti_dump::manifest::PlaylistEntry;
+use std::path::Path;
+
+fn track_record(id: &str) -> TrackRecord {
+    TrackRecord {
+        added_at: Some("2024-01-01T00:00:00Z".to_string()),
+        id: id.to_string(),
+        name: format!("Song {}", id),
+        artists: vec![ArtistRecord {
+            id: None,
+            name: "Artist".to_string(),
+        }],
+        ..Default::default()
+    }
+}
+
+fn tracks(ids: &[&str]) -> Vec<TrackRecord> {
+    ids.iter().map(|id| track_record(id)).collect()
+}
+
+fn ids(changes: &[spoti_dump::diff::TrackChange]) -> Vec<&str> {
+    changes.iter().map(|change| change.id.as_str()).collect()
+}
+
+#[test]
+fn test_diff_tracks_reports_moves_separately() {
+    let old = tracks(&["a", "b", "c", "d", "a"]);
+    let new = tracks(&["b", "c", "a", "e", "d"]);
+
+    let result = diff_tracks(&old, &new);
+    assert_eq!(ids(&result.added), ["e"]);
+    // The second "a" is the one that was removed.
+    assert_eq!(ids(&result.removed), ["a"]);
+    assert_eq!(result.removed[0].position, 5);
+    assert_eq!(ids(&result.moved), ["a"]);
+    assert_eq!(result.moved[0].position, 3);
+}
+
+fn write_dump(dir: &Path, saved: &[&str], playlists: &[(&str, &[&str])]) {
+    format::write_tracks(&dir.join("saved_tracks.csv"), Format::Csv, &tracks(saved)).unwrap();
+    for (name, ids) in playlists {
+        format::write_tracks(&dir.join(format!("{}.csv", name)), Format::Csv, &tracks(ids)).unwrap();
+    }
+}
+
+#[test]
+fn test_diff_legacy_csv_dumps() {
+    let old_dir = tempfile::tempdir().unwrap();
+    let new_dir = tempfile::tempdir().unwrap();
+    write_dump(old_dir.path(), &["a", "b"], &[("Gym", &["a"]), ("Focus", &["b", "c"])]);
+    write_dump(new_dir.path(), &["c", "a", "b"], &[("Focus", &["c", "b"]), ("Party", &["d"])]);
+
+    let read = |dir: &Path| {
+        let source = Source::Dir {
+            path: dir.to_path_buf(),
+            format: None,
+        };
+        (source.read_saved_tracks().unwrap(), source.read_playlists().unwrap())
+    };
+    let (old_saved, old_playlists) = read(old_dir.path());
+    let (new_saved, new_playlists) = read(new_dir.path());
+    let result = diff(&old_saved, &old_playlists, &new_saved, &new_playlists);
+
+    assert_eq!(ids(&result.saved_tracks.added), ["c"]);
+    assert!(result.saved_tracks.moved.is_empty());
+    assert_eq!(result.playlists_added.len(), 1);
+    assert_eq!(result.playlists_added[0].name, "Party");
+    assert_eq!(result.playlists_removed.len(), 1);
+    assert_eq!(result.playlists_removed[0].name, "Gym");
+    assert_eq!(result.playlists_changed.len(), 1);
+    assert_eq!(ids(&result.playlists_changed[0].tracks.moved), ["c"]);
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["playlists_changed"][0]["moved"][0]["id"], "c");
+}
+
+fn playlist(id: &str, name: &str, ids: &[&str]) -> PlaylistDump {
+    PlaylistDump {
+        name: name.to_string(),
+        entry: Some(PlaylistEntry {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }),
+        tracks: tracks(ids),
+    }
+}
+
+#[test]
+fn test_diff_matches_playlists_by_id_then_name() {
+    // "Gym" was renamed; "Focus" was recreated by an import and got a new ID.
+    let old = [playlist("1", "Gym", &["a"]), playlist("2", "Focus", &["b"])];
+    let new = [playlist("3", "Focus", &["b"]), playlist("1", "Workout", &["a"])];
+
+    let result = diff(&[], &old, &[], &new);
+    assert!(result.playlists_added.is_empty());
+    assert!(result.playlists_removed.is_empty());
+    assert_eq!(result.playlists_changed.len(), 1);
+    assert_eq!(result.playlists_changed[0].name, "Workout");
+    assert_eq!(result.playlists_changed[0].renamed_from.as_deref(), Some("Gym"));
+}
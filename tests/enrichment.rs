@@ -0,0 +1,80 @@
+use spoti_dump::export::enrichment::{enrichment_record, lacks_artist_ids, with_artist_ids, ArtistDetails, AudioFeatures};
+use spoti_dump::format::{self, ArtistRecord, EnrichmentRecord, Format, TrackRecord};
+use std::collections::HashMap;
+
+fn track_record(artist_ids: &[&str]) -> TrackRecord {
+    TrackRecord {
+        id: "4uLU6hMCjMI75M1A2tKUQC".to_string(),
+        name: "Never Gonna Give You Up".to_string(),
+        artists: artist_ids
+            .iter()
+            .map(|id| ArtistRecord {
+                id: Some(id.to_string()),
+                name: id.to_string(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn details(genres: &[&str], popularity: u32) -> ArtistDetails {
+    ArtistDetails {
+        genres: genres.iter().map(|genre| genre.to_string()).collect(),
+        popularity: Some(popularity),
+    }
+}
+
+#[test]
+fn test_enrichment_combines_artists_and_features() {
+    let artists = HashMap::from([
+        ("rick".to_string(), details(&["dance rock", "new wave pop"], 70)),
+        ("band".to_string(), details(&["new wave pop", "synthpop"], 40)),
+    ]);
+    let features = AudioFeatures {
+        id: "4uLU6hMCjMI75M1A2tKUQC".to_string(),
+        tempo: Some(113.3),
+        key: Some(-1),
+        mode: Some(1),
+        energy: Some(0.94),
+        danceability: Some(0.72),
+        valence: Some(0.92),
+    };
+
+    let record = enrichment_record(&track_record(&["rick", "band", "unknown"]), &artists, Some(&features));
+    assert_eq!(record.genres, ["dance rock", "new wave pop", "synthpop"]);
+    assert_eq!(record.artist_popularity, Some(70));
+    assert_eq!(record.tempo, Some(113.3));
+    // -1 means Spotify detected no key.
+    assert_eq!(record.key, None);
+
+    let without_features = enrichment_record(&track_record(&["band"]), &artists, None);
+    assert_eq!(without_features.artist_popularity, Some(40));
+    assert_eq!(without_features.energy, None);
+
+    let dump_dir = tempfile::tempdir().unwrap();
+    let csv = dump_dir.path().join("enrichment.csv");
+    let records = vec![record, without_features];
+    format::write_records(&csv, Format::Csv, &records).unwrap();
+    assert_eq!(format::read_records::<EnrichmentRecord>(&csv).unwrap(), records);
+}
+
+#[test]
+fn test_enrichment_recovers_artist_ids_of_reused_records() {
+    // Playlists reused by `--incremental` are read back from CSV, which keeps artist names only.
+    let dump_dir = tempfile::tempdir().unwrap();
+    let csv = dump_dir.path().join("Road_Trip.csv");
+    let exported = track_record(&["rick"]);
+    assert!(!lacks_artist_ids(&exported));
+    format::write_tracks(&csv, Format::Csv, std::slice::from_ref(&exported)).unwrap();
+    let reused = format::read_tracks(&csv).unwrap();
+    assert!(lacks_artist_ids(&reused[0]));
+
+    let artists_by_track = HashMap::from([(exported.id.clone(), exported.artists.clone())]);
+    let tracks = with_artist_ids(reused.iter().collect(), &artists_by_track);
+    assert!(!lacks_artist_ids(&tracks[0]));
+
+    let artists = HashMap::from([("rick".to_string(), details(&["dance rock"], 70))]);
+    let record = enrichment_record(&tracks[0], &artists, None);
+    assert_eq!(record.genres, ["dance rock"]);
+    assert_eq!(record.artist_popularity, Some(70));
+}
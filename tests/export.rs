@@ -1,6 +1,10 @@
 use spoti_dump::export;
+use spoti_dump::format::Format;
+use spoti_dump::select::PlaylistFilter;
 use spoti_dump::access_token;
+use spoti_dump::utils;
 use std::env;
+use std::path::Path;
 
 async fn get_test_access_token() -> String {
     dotenv::dotenv().ok();
@@ -15,13 +19,27 @@ async fn get_test_access_token() -> String {
 #[tokio::test]
 async fn test_export_saved_tracks_force() {
     let access_token = get_test_access_token().await;
-    let result = export::saved_tracks::export_saved_tracks(&access_token, true).await;
+    let result = export::saved_tracks::export_saved_tracks(&access_token, Path::new("dump"), None, Format::Csv, None, true).await;
     assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_export_playlists_force() {
     let access_token = get_test_access_token().await;
-    let result = export::playlists::export_playlists(&access_token, true).await;
+    let user_id = utils::get_user_id(&access_token).await.unwrap();
+    let result = export::playlists::export_playlists(&access_token, &user_id, Path::new("dump"), None, Format::Csv, None, &PlaylistFilter::default(), true).await;
     assert!(result.is_ok());
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_export_followed_artists_force() {
+    let access_token = get_test_access_token().await;
+    let result = export::followed_artists::export_followed_artists(&access_token, Path::new("dump"), Format::Csv, true).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_export_saved_episodes_force() {
+    let access_token = get_test_access_token().await;
+    let result = export::saved_episodes::export_saved_episodes(&access_token, Path::new("dump"), Format::Csv, true).await;
+    assert!(result.is_ok());
+}
@@ -0,0 +1,285 @@
+use spoti_dump::dump::Source;
+use spoti_dump::format::{
+    self, AlbumRecord, ArtistRecord, ArtistProfileRecord, Format, SavedAlbumRecord, SavedAudiobookRecord,
+    SavedEpisodeRecord, TrackRecord,
+};
+use spoti_dump::types::{Audiobook, Episode, Track};
+use spoti_dump::manifest::Manifest;
+use std::fs;
+
+fn track_record() -> TrackRecord {
+    TrackRecord {
+        added_at: Some("2024-01-01T00:00:00Z".to_string()),
+        id: "4uLU6hMCjMI75M1A2tKUQC".to_string(),
+        name: "Never Gonna Give You Up".to_string(),
+        artists: vec![
+            ArtistRecord {
+                id: Some("0gxyHStUsqpMadRV0Di1Qt".to_string()),
+                name: "Rick Astley".to_string(),
+            },
+            ArtistRecord {
+                id: None,
+                name: "Earth, Wind & Fire".to_string(),
+            },
+        ],
+        album: AlbumRecord {
+            id: Some("6N9PS4QXF1D0OWPk0Sxtb4".to_string()),
+            name: "Whenever You Need Somebody".to_string(),
+            release_date: Some("1987-11-12".to_string()),
+        },
+        uri: Some("spotify:track:4uLU6hMCjMI75M1A2tKUQC".to_string()),
+        duration_ms: Some(213573),
+        explicit: Some(false),
+        isrc: Some("GBARL9300135".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_json_formats_round_trip() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let tracks = vec![track_record()];
+
+    for format in [Format::Json, Format::Jsonl] {
+        let path = dump_dir
+            .path()
+            .join(format!("saved_tracks.{}", format.extension()));
+        format::write_tracks(&path, format, &tracks).unwrap();
+        assert_eq!(format::read_tracks(&path).unwrap(), tracks);
+    }
+}
+
+#[test]
+fn test_csv_round_trip_keeps_ids() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let path = dump_dir.path().join("saved_tracks.csv");
+    format::write_tracks(&path, Format::Csv, &[track_record()]).unwrap();
+
+    let tracks = format::read_tracks(&path).unwrap();
+    assert_eq!(tracks.len(), 1);
+    assert_eq!(tracks[0].id, "4uLU6hMCjMI75M1A2tKUQC");
+    assert_eq!(tracks[0].duration_ms, Some(213573));
+    assert_eq!(tracks[0].uri(), "spotify:track:4uLU6hMCjMI75M1A2tKUQC");
+}
+
+#[test]
+fn test_csv_keeps_playlist_item_details() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let mut added = track_record();
+    added.added_by = Some("alice".to_string());
+    added.position = Some(0);
+    let mut local = track_record();
+    local.id = String::new();
+    local.uri = Some("spotify:local:Me:Demos:Home+Recording:120".to_string());
+    local.added_by = Some("bob".to_string());
+    local.position = Some(1);
+    local.is_local = true;
+    let tracks = vec![added, local];
+
+    let path = dump_dir.path().join("Road_Trip.csv");
+    format::write_tracks(&path, Format::Csv, &tracks).unwrap();
+    let read = format::read_tracks(&path).unwrap();
+    assert_eq!(read[0].added_by.as_deref(), Some("alice"));
+    assert!(!read[0].is_local);
+    assert_eq!(read[1].position, Some(1));
+    assert!(read[1].is_local);
+    assert_eq!(read[1].uri(), "spotify:local:Me:Demos:Home+Recording:120");
+}
+
+#[test]
+fn test_csv_keeps_track_availability() {
+    // Relinked to another version in the market; the saved track is what gets recorded.
+    let track: Track = serde_json::from_value(serde_json::json!({
+        "id": "6kLCHFM39wkFjOuyPGLGeQ",
+        "name": "Heaven and Hell",
+        "artists": [{ "id": "0ElvsoF2CjJwBJ6xkHsJ8v", "name": "William Onyeabor" }],
+        "album": { "id": "1uNFoZAHBGtllmzznpCI3s", "name": "Who Is William Onyeabor?" },
+        "uri": "spotify:track:6kLCHFM39wkFjOuyPGLGeQ",
+        "is_playable": true,
+        "linked_from": { "id": "6ozxplTAjWO0BlUxN8ia0A", "uri": "spotify:track:6ozxplTAjWO0BlUxN8ia0A" }
+    }))
+    .unwrap();
+    let relinked = TrackRecord::from_track(None, track).unwrap();
+    assert_eq!(relinked.id, "6ozxplTAjWO0BlUxN8ia0A");
+    assert_eq!(relinked.uri(), "spotify:track:6ozxplTAjWO0BlUxN8ia0A");
+
+    let mut restricted = track_record();
+    restricted.is_playable = Some(false);
+    restricted.restriction = Some("market".to_string());
+
+    let dump_dir = tempfile::tempdir().unwrap();
+    let path = dump_dir.path().join("saved_tracks.csv");
+    format::write_tracks(&path, Format::Csv, &[relinked, restricted, track_record()]).unwrap();
+    let read = format::read_tracks(&path).unwrap();
+    assert_eq!(read[0].is_playable, Some(true));
+    assert_eq!(read[1].is_playable, Some(false));
+    assert_eq!(read[1].restriction.as_deref(), Some("market"));
+    assert_eq!(read[2].is_playable, None);
+}
+
+#[test]
+fn test_read_legacy_csv() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let path = dump_dir.path().join("saved_tracks.csv");
+    fs::write(
+        &path,
+        "Added At,Track Name,Artists,Album,Id\nUnknown,Song,\"A, B\",Unknown,abc123\n",
+    )
+    .unwrap();
+
+    let tracks = format::read_tracks(&path).unwrap();
+    assert_eq!(tracks.len(), 1);
+    assert_eq!(tracks[0].added_at, None);
+    assert_eq!(tracks[0].artists.len(), 2);
+    assert_eq!(tracks[0].uri(), "spotify:track:abc123");
+}
+
+fn saved_album_record() -> SavedAlbumRecord {
+    SavedAlbumRecord {
+        added_at: Some("2024-01-01T00:00:00Z".to_string()),
+        id: "6N9PS4QXF1D0OWPk0Sxtb4".to_string(),
+        name: "Whenever You Need Somebody".to_string(),
+        artists: vec![ArtistRecord {
+            id: Some("0gxyHStUsqpMadRV0Di1Qt".to_string()),
+            name: "Rick Astley".to_string(),
+        }],
+        album_type: Some("album".to_string()),
+        release_date: Some("1987-11-12".to_string()),
+        total_tracks: Some(10),
+        label: Some("RCA".to_string()),
+        upc: Some("886445261419".to_string()),
+        uri: Some("spotify:album:6N9PS4QXF1D0OWPk0Sxtb4".to_string()),
+    }
+}
+
+#[test]
+fn test_saved_albums_round_trip() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let albums = vec![saved_album_record()];
+
+    let jsonl = dump_dir.path().join("saved_albums.jsonl");
+    format::write_records(&jsonl, Format::Jsonl, &albums).unwrap();
+    assert_eq!(format::read_records::<SavedAlbumRecord>(&jsonl).unwrap(), albums);
+
+    // CSV keeps the columns people look at; the rest is lost.
+    let csv = dump_dir.path().join("saved_albums.csv");
+    format::write_records(&csv, Format::Csv, &albums).unwrap();
+    let read = format::read_records::<SavedAlbumRecord>(&csv).unwrap();
+    assert_eq!(read[0].id, albums[0].id);
+    assert_eq!(read[0].artists[0].name, "Rick Astley");
+    assert_eq!(read[0].total_tracks, Some(10));
+    assert_eq!(read[0].label, None);
+}
+
+#[test]
+fn test_components_keep_their_own_format() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let source = Source::Dir {
+        path: dump_dir.path().to_path_buf(),
+        format: None,
+    };
+
+    // Exported with `--only saved-albums --format json`: no saved tracks at all.
+    format::write_records(&dump_dir.path().join("saved_albums.json"), Format::Json, &[saved_album_record()]).unwrap();
+    let mut manifest = Manifest::default();
+    manifest.formats.insert("saved_albums".to_string(), Format::Json);
+    manifest.save(dump_dir.path()).unwrap();
+    assert!(source.read_saved_tracks().unwrap().is_empty());
+    assert_eq!(source.read_saved_albums().unwrap().len(), 1);
+
+    // Then `--only saved-tracks` in CSV over the same folder.
+    format::write_tracks(&dump_dir.path().join("saved_tracks.csv"), Format::Csv, &[track_record()]).unwrap();
+    manifest.formats.insert("saved_tracks".to_string(), Format::Csv);
+    manifest.save(dump_dir.path()).unwrap();
+    assert_eq!(source.read_saved_tracks().unwrap().len(), 1);
+    assert_eq!(source.read_saved_albums().unwrap().len(), 1);
+}
+
+#[test]
+fn test_component_files_are_not_playlists() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    Manifest::default().save(dump_dir.path()).unwrap();
+    format::write_tracks(&dump_dir.path().join("saved_tracks.csv"), Format::Csv, &[track_record()]).unwrap();
+    format::write_records(&dump_dir.path().join("saved_albums.csv"), Format::Csv, &[saved_album_record()]).unwrap();
+
+    let source = Source::Dir {
+        path: dump_dir.path().to_path_buf(),
+        format: None,
+    };
+    assert!(source.read_playlists().unwrap().is_empty());
+    assert_eq!(source.read_saved_albums().unwrap().len(), 1);
+}
+
+#[test]
+fn test_followed_artists_round_trip() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let artists = vec![ArtistProfileRecord {
+        id: "0gxyHStUsqpMadRV0Di1Qt".to_string(),
+        name: "Rick Astley".to_string(),
+        genres: vec!["dance rock".to_string(), "new wave pop".to_string()],
+        popularity: Some(70),
+        followers: Some(2_500_000),
+        uri: Some("spotify:artist:0gxyHStUsqpMadRV0Di1Qt".to_string()),
+    }];
+
+    let json = dump_dir.path().join("followed_artists.json");
+    format::write_records(&json, Format::Json, &artists).unwrap();
+    assert_eq!(format::read_records::<ArtistProfileRecord>(&json).unwrap(), artists);
+
+    let csv = dump_dir.path().join("followed_artists.csv");
+    format::write_records(&csv, Format::Csv, &artists).unwrap();
+    let read = format::read_records::<ArtistProfileRecord>(&csv).unwrap();
+    assert_eq!(read[0].id, artists[0].id);
+    assert_eq!(read[0].genres, artists[0].genres);
+    assert_eq!(read[0].followers, None);
+}
+
+#[test]
+fn test_saved_episodes_keep_resume_points() {
+    let episode: Episode = serde_json::from_value(serde_json::json!({
+        "id": "512ojhOuo1ktJprKbVcKyQ",
+        "name": "Episode 42",
+        "release_date": "2024-02-01",
+        "duration_ms": 3600000,
+        "uri": "spotify:episode:512ojhOuo1ktJprKbVcKyQ",
+        "resume_point": { "fully_played": false, "resume_position_ms": 1234000 },
+        "show": { "id": "38bS44xjbVVZ3No3ByF1dJ", "name": "Some Podcast" }
+    }))
+    .unwrap();
+    let record = SavedEpisodeRecord::from_episode(Some("2024-02-02T00:00:00Z".to_string()), episode).unwrap();
+    assert_eq!(record.show_name.as_deref(), Some("Some Podcast"));
+    assert!(record.has_progress());
+
+    let dump_dir = tempfile::tempdir().unwrap();
+    let csv = dump_dir.path().join("saved_episodes.csv");
+    format::write_records(&csv, Format::Csv, std::slice::from_ref(&record)).unwrap();
+    let read = format::read_records::<SavedEpisodeRecord>(&csv).unwrap();
+    assert_eq!(read[0].resume_position_ms, Some(1234000));
+    assert_eq!(read[0].fully_played, Some(false));
+    assert_eq!(read[0].show_id, None);
+}
+
+#[test]
+fn test_saved_audiobooks_round_trip() {
+    let audiobook: Audiobook = serde_json::from_value(serde_json::json!({
+        "id": "7iHfbu1YPACw6oZPAFJtqe",
+        "name": "Dune",
+        "authors": [{ "name": "Frank Herbert" }],
+        "narrators": [{ "name": "Scott Brick" }, { "name": "Orlagh Cassidy" }],
+        "publisher": "Macmillan Audio",
+        "total_chapters": 49,
+        "uri": "spotify:audiobook:7iHfbu1YPACw6oZPAFJtqe"
+    }))
+    .unwrap();
+    let record = SavedAudiobookRecord::from_audiobook(Some("2024-03-01T00:00:00Z".to_string()), audiobook).unwrap();
+    assert_eq!(record.narrators, ["Scott Brick", "Orlagh Cassidy"]);
+
+    let dump_dir = tempfile::tempdir().unwrap();
+    let csv = dump_dir.path().join("saved_audiobooks.csv");
+    format::write_records(&csv, Format::Csv, std::slice::from_ref(&record)).unwrap();
+    let read = format::read_records::<SavedAudiobookRecord>(&csv).unwrap();
+    assert_eq!(read[0].authors, record.authors);
+    assert_eq!(read[0].narrators, record.narrators);
+    assert_eq!(read[0].added_at, record.added_at);
+    assert_eq!(read[0].total_chapters, None);
+}
@@ -0,0 +1,64 @@
+use spoti_dump::history::{commit_export, summarize};
+use spoti_dump::manifest::{Manifest, PlaylistEntry};
+use std::fs;
+
+fn playlist_entry(id: &str, name: &str, track_count: u32) -> PlaylistEntry {
+    PlaylistEntry {
+        file: format!("{}.csv", name),
+        id: id.to_string(),
+        name: name.to_string(),
+        public: Some(false),
+        track_count: Some(track_count),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_summarize_playlist_changes() {
+    let previous = Manifest {
+        playlists: vec![
+            playlist_entry("a", "Road_Trip", 10),
+            playlist_entry("b", "Gym", 5),
+        ],
+        saved_tracks_total: Some(100),
+        ..Default::default()
+    };
+    let current = Manifest {
+        playlists: vec![
+            playlist_entry("a", "Road_Trip_2024", 12),
+            playlist_entry("c", "Focus", 3),
+        ],
+        saved_tracks_total: Some(102),
+        ..Default::default()
+    };
+
+    let message = summarize(Some(&previous), &current);
+    let subject = message.lines().next().unwrap();
+    assert!(subject.ends_with("playlists 1 added, 1 removed, 1 changed; +2 saved tracks"));
+    assert!(message.contains("Renamed playlist 'Road_Trip' to 'Road_Trip_2024'"));
+    assert!(message.contains("Playlist 'Road_Trip_2024': 10 -> 12 tracks"));
+    assert!(message.contains("Added playlist 'Focus' (3 tracks)"));
+    assert!(message.contains("Removed playlist 'Gym'"));
+}
+
+#[test]
+fn test_commit_export_without_remote() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let mut manifest = Manifest {
+        playlists: vec![playlist_entry("a", "Road_Trip", 1)],
+        ..Default::default()
+    };
+    manifest.save(dump_dir.path()).unwrap();
+    fs::write(dump_dir.path().join("Road_Trip.csv"), "Added At\n").unwrap();
+
+    let message = commit_export(dump_dir.path()).unwrap().unwrap();
+    assert!(message.starts_with("Initial export"));
+    assert!(dump_dir.path().join(".git").is_dir());
+    assert!(commit_export(dump_dir.path()).unwrap().is_none());
+
+    manifest.playlists.clear();
+    manifest.save(dump_dir.path()).unwrap();
+    fs::remove_file(dump_dir.path().join("Road_Trip.csv")).unwrap();
+    let message = commit_export(dump_dir.path()).unwrap().unwrap();
+    assert!(message.contains("Removed playlist 'Road_Trip'"));
+}
@@ -1,4 +1,6 @@
+use spoti_dump::dump::Source;
 use spoti_dump::import;
+use spoti_dump::select::PlaylistFilter;
 use spoti_dump::access_token;
 use spoti_dump::utils;
 use std::env;
@@ -16,7 +18,7 @@ async fn get_test_access_token() -> String {
 #[tokio::test]
 async fn test_import_saved_tracks_dry_run() {
     let access_token = get_test_access_token().await;
-    let result = import::saved_tracks::import_saved_tracks(&access_token, false).await;
+    let result = import::saved_tracks::import_saved_tracks(&access_token, &Source::default(), false).await;
     assert!(result.is_ok());
 }
 
@@ -24,6 +26,6 @@ async fn test_import_saved_tracks_dry_run() {
 async fn test_import_playlists_dry_run() {
     let access_token = get_test_access_token().await;
     let user_id = utils::get_user_id(&access_token).await.unwrap();
-    let result = import::playlists::import_playlists(&access_token, &user_id, false).await;
+    let result = import::playlists::import_playlists(&access_token, &user_id, &Source::default(), &PlaylistFilter::default(), false, false).await;
     assert!(result.is_ok());
 }
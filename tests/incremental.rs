@@ -0,0 +1,100 @@
+use spoti_dump::export::playlists::{merge_playlist_entries, playlist_file_names};
+use spoti_dump::export::saved_tracks::merge_saved_tracks;
+use spoti_dump::format::{Format, TrackRecord};
+use spoti_dump::manifest::PlaylistEntry;
+use spoti_dump::select::PlaylistFilter;
+
+fn track_record(id: &str, added_at: &str) -> TrackRecord {
+    TrackRecord {
+        added_at: Some(added_at.to_string()),
+        id: id.to_string(),
+        name: id.to_uppercase(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_merge_saved_tracks_puts_new_tracks_first() {
+    let previous = vec![
+        track_record("b", "2024-01-02T00:00:00Z"),
+        track_record("a", "2024-01-01T00:00:00Z"),
+    ];
+    // "a" was removed and saved again, so it moves to the top.
+    let new = vec![
+        track_record("c", "2024-02-02T00:00:00Z"),
+        track_record("a", "2024-02-01T00:00:00Z"),
+    ];
+
+    let merged = merge_saved_tracks(new, previous);
+    let ids: Vec<&str> = merged.iter().map(|track| track.id.as_str()).collect();
+    assert_eq!(ids, ["c", "a", "b"]);
+    assert_eq!(merged[1].added_at.as_deref(), Some("2024-02-01T00:00:00Z"));
+}
+
+#[test]
+fn test_playlists_keep_their_previous_files() {
+    let previous = [
+        PlaylistEntry {
+            file: "Chill.csv".to_string(),
+            id: "a".to_string(),
+            name: "Chill!".to_string(),
+            ..Default::default()
+        },
+        PlaylistEntry {
+            file: "Chill_b.csv".to_string(),
+            id: "b".to_string(),
+            name: "Chill?".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    // Spotify now lists "Chill?" first; it must not take over the file of "Chill!".
+    let names = playlist_file_names(&[("b", "Chill?"), ("c", "Chill"), ("a", "Chill!")], &previous, &[], Format::Csv);
+    assert_eq!(names, ["Chill_b.csv", "Chill_c.csv", "Chill.csv"]);
+
+    let names = playlist_file_names(&[("b", "Chill?"), ("a", "Chill!")], &previous, &[], Format::Json);
+    assert_eq!(names, ["Chill_b.json", "Chill.json"]);
+}
+
+fn playlist_entry(id: &str, name: &str, file: &str) -> PlaylistEntry {
+    PlaylistEntry {
+        file: file.to_string(),
+        id: id.to_string(),
+        name: name.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_filtered_export_keeps_other_playlists() {
+    let existing = [
+        playlist_entry("a", "Chill", "Chill.csv"),
+        playlist_entry("b", "Road Trip", "Road_Trip.csv"),
+        playlist_entry("c", "Gym", "Gym.csv"),
+    ];
+    let filter = PlaylistFilter {
+        include_ids: vec!["b".to_string(), "c".to_string(), "d".to_string()],
+        ..Default::default()
+    };
+    // "b" was exported again, "c" is gone from Spotify and "d" is new.
+    let exported = vec![
+        playlist_entry("d", "Focus", "Focus.csv"),
+        playlist_entry("b", "Road Trip 2024", "Road_Trip.csv"),
+    ];
+
+    let merged = merge_playlist_entries(&existing, exported.clone(), &filter);
+    let ids: Vec<&str> = merged.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b", "d"]);
+    assert_eq!(merged[1].name, "Road Trip 2024");
+
+    let replaced = merge_playlist_entries(&existing, exported, &PlaylistFilter::default());
+    let ids: Vec<&str> = replaced.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, ["d", "b"]);
+}
+
+#[test]
+fn test_files_of_unselected_playlists_are_not_taken() {
+    let unselected = [playlist_entry("a", "Chill!", "Chill.csv")];
+    let names = playlist_file_names(&[("b", "Chill?")], &[], &unselected, Format::Csv);
+    assert_eq!(names, ["Chill_b.csv"]);
+}
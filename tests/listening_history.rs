@@ -0,0 +1,41 @@
+use spoti_dump::export::listening_history::last_played_at;
+use spoti_dump::format::{self, Format, PlayRecord};
+use std::fs;
+
+fn play(played_at: &str) -> PlayRecord {
+    PlayRecord {
+        played_at: played_at.to_string(),
+        id: "4uLU6hMCjMI75M1A2tKUQC".to_string(),
+        name: "Never Gonna Give You Up".to_string(),
+        artists: Vec::new(),
+        album: Default::default(),
+        uri: None,
+        duration_ms: None,
+        context_uri: Some("spotify:playlist:37i9dQZF1DXcBWIGoYBM5M".to_string()),
+    }
+}
+
+#[test]
+fn test_last_played_at_spans_files_and_formats() {
+    let history_dir = tempfile::tempdir().unwrap();
+    assert_eq!(last_played_at(history_dir.path()).unwrap(), None);
+
+    let dir = history_dir.path();
+    format::write_records(
+        &dir.join("recently_played_2024-03-01T00-00-00Z.csv"),
+        Format::Csv,
+        &[play("2024-02-29T22:10:00.123Z"), play("2024-02-29T21:00:00Z")],
+    )
+    .unwrap();
+    format::write_records(
+        &dir.join("recently_played_2024-02-01T00-00-00Z.jsonl"),
+        Format::Jsonl,
+        &[play("2024-01-31T10:00:00Z")],
+    )
+    .unwrap();
+    // Top lists are not plays.
+    fs::write(dir.join("top_tracks_short_term_2024-03-01.csv"), "Added At\n").unwrap();
+
+    let last = last_played_at(dir).unwrap().unwrap();
+    assert_eq!(last.to_rfc3339(), "2024-02-29T22:10:00.123+00:00");
+}
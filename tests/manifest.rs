@@ -0,0 +1,150 @@
+use spoti_dump::dump::Source;
+use spoti_dump::export::playlists::contributors;
+use spoti_dump::format::{Format, TrackRecord};
+use spoti_dump::manifest::{Contributor, Manifest, PlaylistEntry, Profile, MANIFEST_FILE, SCHEMA_VERSION};
+use std::fs;
+use spoti_dump::types::{Owner, User};
+
+fn playlist_entry(file: &str, name: &str) -> PlaylistEntry {
+    PlaylistEntry {
+        file: file.to_string(),
+        id: "37i9dQZF1DXcBWIGoYBM5M".to_string(),
+        name: name.to_string(),
+        description: Some("Road trip songs".to_string()),
+        public: Some(true),
+        owner: Owner {
+            id: "owner".to_string(),
+            display_name: Some("Owner".to_string()),
+        },
+        snapshot_id: Some("snapshot".to_string()),
+        followers: Some(42),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_manifest_round_trip() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let manifest = Manifest {
+        playlists: vec![playlist_entry("Road_Trip.csv", "Road Trip")],
+        ..Default::default()
+    };
+    manifest.save(dump_dir.path()).unwrap();
+
+    let loaded = Manifest::load(dump_dir.path()).unwrap();
+    assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+    let entry = &loaded.playlists[0];
+    assert_eq!(entry.file, "Road_Trip.csv");
+    assert_eq!(entry.name, "Road Trip");
+    assert_eq!(entry.description.as_deref(), Some("Road trip songs"));
+    assert_eq!(entry.public, Some(true));
+    assert_eq!(entry.followers, Some(42));
+}
+
+#[test]
+fn test_markets_are_kept_per_component() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let mut manifest = Manifest::default();
+    manifest.set_component_market("saved_tracks", Some("SE"));
+    manifest.set_component_market("playlists", Some("from_token"));
+    manifest.save(dump_dir.path()).unwrap();
+
+    // Exporting the playlists alone later, without a market, leaves the saved tracks' market.
+    let mut manifest = Manifest::load(dump_dir.path()).unwrap();
+    manifest.set_component_market("playlists", None);
+    manifest.save(dump_dir.path()).unwrap();
+
+    let loaded = Manifest::load(dump_dir.path()).unwrap();
+    assert_eq!(loaded.component_market("saved_tracks"), Some("SE"));
+    assert_eq!(loaded.component_market("playlists"), None);
+    let source = Source::Dir {
+        path: dump_dir.path().to_path_buf(),
+        format: None,
+    };
+    assert_eq!(source.read_markets().unwrap(), ["SE"]);
+}
+
+#[test]
+fn test_manifest_missing_is_empty() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let manifest = Manifest::load(dump_dir.path()).unwrap();
+    assert!(manifest.playlists.is_empty());
+}
+
+#[test]
+fn test_manifest_rejects_newer_schema() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    let contents = format!(r#"{{"schema_version": {}, "playlists": []}}"#, SCHEMA_VERSION + 1);
+    fs::write(dump_dir.path().join(MANIFEST_FILE), contents).unwrap();
+
+    assert!(Manifest::load(dump_dir.path()).is_err());
+}
+
+#[test]
+fn test_manifest_version_check() {
+    let dump_dir = tempfile::tempdir().unwrap();
+    Manifest::default().save(dump_dir.path()).unwrap();
+    let saved = fs::read_to_string(dump_dir.path().join(MANIFEST_FILE)).unwrap();
+    assert!(saved.contains(r#""schema_version": 2"#));
+
+    // Dumps of the first schema, all CSV, still load.
+    fs::write(dump_dir.path().join(MANIFEST_FILE), r#"{"schema_version": 1, "playlists": []}"#).unwrap();
+    let manifest = Manifest::load(dump_dir.path()).unwrap();
+    assert_eq!(manifest.schema_version, 1);
+    assert_eq!(manifest.component_format("saved_tracks"), Format::Csv);
+    manifest.save(dump_dir.path()).unwrap();
+    assert_eq!(Manifest::load(dump_dir.path()).unwrap().schema_version, SCHEMA_VERSION);
+
+    fs::write(dump_dir.path().join(MANIFEST_FILE), r#"{"schema_version": 3, "playlists": []}"#).unwrap();
+    let error = Manifest::load(dump_dir.path()).unwrap_err();
+    assert!(error.to_string().contains("Please update spoti-dump"));
+}
+
+#[test]
+fn test_profile_round_trip() {
+    let user: User = serde_json::from_value(serde_json::json!({
+        "id": "wizzler",
+        "display_name": "Wizzler",
+        "country": "SE",
+        "product": "premium",
+        "followers": { "href": null, "total": 12 },
+        "images": [{ "url": "https://i.scdn.co/image/ab67", "height": 300, "width": 300 }],
+        "explicit_content": { "filter_enabled": true, "filter_locked": false }
+    }))
+    .unwrap();
+
+    let dump_dir = tempfile::tempdir().unwrap();
+    let manifest = Manifest {
+        profile: Some(Profile::from(user)),
+        ..Default::default()
+    };
+    manifest.save(dump_dir.path()).unwrap();
+
+    let source = Source::Dir {
+        path: dump_dir.path().to_path_buf(),
+        format: None,
+    };
+    let profile = source.read_profile().unwrap().unwrap();
+    assert_eq!(profile.name(), "Wizzler");
+    assert_eq!(profile.country.as_deref(), Some("SE"));
+    assert_eq!(profile.followers, Some(12));
+    assert!(profile.explicit_content.unwrap().filter_enabled);
+}
+
+#[test]
+fn test_contributors_are_counted_most_first() {
+    let track = |added_by: Option<&str>| TrackRecord {
+        id: "4uLU6hMCjMI75M1A2tKUQC".to_string(),
+        added_by: added_by.map(String::from),
+        ..Default::default()
+    };
+    let tracks = [track(Some("bob")), track(Some("alice")), track(None), track(Some("bob"))];
+
+    assert_eq!(
+        contributors(&tracks),
+        [
+            Contributor { user_id: "bob".to_string(), tracks: 2 },
+            Contributor { user_id: "alice".to_string(), tracks: 1 },
+        ]
+    );
+}
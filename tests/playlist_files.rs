@@ -0,0 +1,22 @@
+use spoti_dump::export::playlists::playlist_file_names;
+use spoti_dump::format::Format;
+
+#[test]
+fn test_clashing_names_get_the_playlist_id() {
+    let names = playlist_file_names(&[("a", "Chill!"), ("b", "Chill?"), ("c", "chill")], &[], &[], Format::Csv);
+    // File systems on Windows and macOS ignore case, so "chill" clashes too.
+    assert_eq!(names, ["Chill.csv", "Chill_b.csv", "chill_c.csv"]);
+}
+
+#[test]
+fn test_names_without_usable_characters_use_the_id() {
+    let names = playlist_file_names(&[("a", "🔥🔥🔥"), ("b", "Road Trip ☀️")], &[], &[], Format::Json);
+    assert_eq!(names, ["a.json", "Road_Trip_.json"]);
+}
+
+#[test]
+fn test_names_of_other_dump_files_are_reserved() {
+    let names = playlist_file_names(&[("a", "Saved Tracks"), ("b", "saved albums"), ("c", "manifest")], &[], &[], Format::Json);
+    // "manifest.json" belongs to the manifest, "saved_tracks.json" to the saved tracks.
+    assert_eq!(names, ["Saved_Tracks_a.json", "saved_albums_b.json", "manifest_c.json"]);
+}
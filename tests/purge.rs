@@ -1,4 +1,5 @@
 use spoti_dump::purge;
+use spoti_dump::select::PlaylistFilter;
 use spoti_dump::access_token;
 use std::env;
 
@@ -22,6 +23,6 @@ async fn test_purge_saved_tracks_dry_run() {
 #[tokio::test]
 async fn test_purge_playlists_dry_run() {
     let access_token = get_test_access_token().await;
-    let result = purge::playlists::purge_playlists(&access_token, false).await;
+    let result = purge::playlists::purge_playlists(&access_token, &PlaylistFilter::default(), false).await;
     assert!(result.is_ok());
 }
@@ -0,0 +1,59 @@
+use spoti_dump::select::{Component, PlaylistFilter, Selection};
+
+fn filter(configure: impl FnOnce(&mut PlaylistFilter)) -> PlaylistFilter {
+    let mut filter = PlaylistFilter::default();
+    configure(&mut filter);
+    filter
+}
+
+#[test]
+fn test_name_patterns() {
+    let globs = filter(|filter| filter.include_names = vec!["road*".parse().unwrap()]);
+    assert!(globs.matches(None, "Road Trip", None));
+    assert!(!globs.matches(None, "Off Road", None));
+
+    let regex = filter(|filter| {
+        filter.include_names = vec!["re:^(gym|run)".parse().unwrap()];
+        filter.exclude_names = vec!["*2019*".parse().unwrap()];
+    });
+    assert!(regex.matches(None, "Running", None));
+    assert!(!regex.matches(None, "Gym 2019", None));
+    assert!(!regex.matches(None, "Focus", None));
+
+    assert!("re:(".parse::<spoti_dump::select::NamePattern>().is_err());
+}
+
+#[test]
+fn test_ids_and_owners() {
+    let owned = filter(|filter| {
+        filter.include_owners = vec!["me".to_string()];
+        filter.exclude_ids = vec!["skip".to_string()];
+    });
+    assert!(owned.matches(Some("keep"), "Mine", Some("me")));
+    assert!(!owned.matches(Some("skip"), "Mine", Some("me")));
+    assert!(!owned.matches(Some("other"), "Theirs", Some("friend")));
+    // Legacy dumps know neither the ID nor the owner.
+    assert!(!owned.matches(None, "Mine", None));
+    assert!(PlaylistFilter::default().matches(None, "Anything", None));
+}
+
+#[test]
+fn test_only_components() {
+    let all = Selection::default();
+    assert!(all.includes(Component::SavedTracks) && all.includes(Component::Playlists));
+    assert!(!all.includes(Component::ListeningHistory));
+
+    let playlists = Selection {
+        only: vec![Component::Playlists],
+        ..Default::default()
+    };
+    assert!(!playlists.includes(Component::SavedTracks));
+    assert!(playlists.includes(Component::Playlists));
+
+    let history = Selection {
+        only: vec![Component::ListeningHistory, Component::SavedTracks],
+        ..Default::default()
+    };
+    assert!(history.includes(Component::ListeningHistory));
+    assert!(!history.includes(Component::Playlists));
+}
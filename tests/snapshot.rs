@@ -0,0 +1,82 @@
+use chrono::{TimeZone, Utc};
+use spoti_dump::dump::DumpDir;
+use spoti_dump::snapshot::{self, Retention, Snapshot};
+use std::fs;
+
+fn snapshot(year: i32, month: u32, day: u32, hour: u32) -> Snapshot {
+    let taken_at = Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap();
+    Snapshot {
+        name: taken_at.format("%Y-%m-%dT%H-%M-%SZ").to_string(),
+        taken_at,
+    }
+}
+
+#[test]
+fn test_select_kept_applies_retention_rules() {
+    // Newest first, as `snapshot::list` returns them.
+    let snapshots = vec![
+        snapshot(2024, 3, 10, 18),
+        snapshot(2024, 3, 10, 6),
+        snapshot(2024, 3, 9, 6),
+        snapshot(2024, 3, 1, 6),
+        snapshot(2024, 2, 20, 6),
+        snapshot(2024, 1, 15, 6),
+    ];
+
+    let kept = snapshot::select_kept(
+        &snapshots,
+        Retention {
+            daily: 2,
+            weekly: 0,
+            monthly: 2,
+        },
+    );
+
+    let mut kept: Vec<&str> = kept.iter().map(String::as_str).collect();
+    kept.sort();
+    assert_eq!(
+        kept,
+        ["2024-02-20T06-00-00Z", "2024-03-09T06-00-00Z", "2024-03-10T18-00-00Z"]
+    );
+}
+
+#[test]
+fn test_prune_and_latest_pointer() {
+    let root = tempfile::tempdir().unwrap();
+    for snapshot in [snapshot(2024, 3, 10, 6), snapshot(2024, 3, 9, 6)] {
+        fs::create_dir(root.path().join(&snapshot.name)).unwrap();
+    }
+    let newest = root.path().join("2024-03-10T06-00-00Z");
+    snapshot::mark_latest(root.path(), &newest).unwrap();
+    assert_eq!(DumpDir::open(root.path()).unwrap().path(), newest);
+
+    let retention = Retention {
+        daily: 1,
+        ..Default::default()
+    };
+    assert_eq!(snapshot::prune(root.path(), retention, false).unwrap(), ["2024-03-09T06-00-00Z"]);
+    assert!(root.path().join("2024-03-09T06-00-00Z").exists());
+
+    snapshot::prune(root.path(), retention, true).unwrap();
+    assert!(!root.path().join("2024-03-09T06-00-00Z").exists());
+    assert!(newest.exists());
+}
+
+#[test]
+fn test_prune_keeps_latest_behind_failed_export() {
+    let root = tempfile::tempdir().unwrap();
+    // The 11:00 export failed before it was marked as the latest.
+    for snapshot in [snapshot(2026, 10, 19, 11), snapshot(2026, 10, 19, 10), snapshot(2026, 10, 18, 10)] {
+        fs::create_dir(root.path().join(&snapshot.name)).unwrap();
+    }
+    let latest = root.path().join("2026-10-19T10-00-00Z");
+    snapshot::mark_latest(root.path(), &latest).unwrap();
+
+    let retention = Retention {
+        daily: 1,
+        ..Default::default()
+    };
+    assert_eq!(snapshot::prune(root.path(), retention, true).unwrap(), ["2026-10-18T10-00-00Z"]);
+    assert!(latest.exists());
+    assert_eq!(DumpDir::open(root.path()).unwrap().path(), latest);
+}
@@ -0,0 +1,208 @@
+use spoti_dump::dump::{PlaylistDump, Source};
+use spoti_dump::format::{AlbumRecord, ArtistRecord, TrackRecord};
+use spoti_dump::manifest::PlaylistEntry;
+use spoti_dump::sqlite::Library;
+
+fn track_record(id: &str, name: &str) -> TrackRecord {
+    TrackRecord {
+        added_at: Some("2024-01-01T00:00:00Z".to_string()),
+        id: id.to_string(),
+        name: name.to_string(),
+        artists: vec![ArtistRecord {
+            id: Some("artist".to_string()),
+            name: "Artist".to_string(),
+        }],
+        album: AlbumRecord {
+            id: Some("album".to_string()),
+            name: "Album".to_string(),
+            release_date: None,
+        },
+        uri: Some(format!("spotify:track:{}", id)),
+        duration_ms: Some(180000),
+        explicit: Some(false),
+        ..Default::default()
+    }
+}
+
+fn playlist_dump(tracks: Vec<TrackRecord>) -> PlaylistDump {
+    PlaylistDump {
+        name: "Road Trip".to_string(),
+        entry: Some(PlaylistEntry {
+            file: "Road_Trip.csv".to_string(),
+            id: "playlist".to_string(),
+            name: "Road Trip".to_string(),
+            description: Some("Songs for the road".to_string()),
+            public: Some(false),
+            ..Default::default()
+        }),
+        tracks,
+    }
+}
+
+#[test]
+fn test_sqlite_reads_latest_export() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library.sqlite");
+
+    let mut library = Library::open(&path).unwrap();
+    library
+        .write_saved_tracks(&[track_record("a", "First")])
+        .unwrap();
+    library
+        .write_playlists(&[playlist_dump(vec![track_record("a", "First")])])
+        .unwrap();
+    library
+        .write_saved_tracks(&[track_record("a", "Renamed"), track_record("b", "Second")])
+        .unwrap();
+    library
+        .write_playlists(&[playlist_dump(vec![
+            track_record("b", "Second"),
+            track_record("a", "Renamed"),
+        ])])
+        .unwrap();
+    drop(library);
+
+    let source = Source::Sqlite(path);
+    let saved_tracks = source.read_saved_tracks().unwrap();
+    assert_eq!(saved_tracks.len(), 2);
+    assert_eq!(saved_tracks[0].name, "Renamed");
+    assert_eq!(saved_tracks[0].artists[0].name, "Artist");
+
+    let playlists = source.read_playlists().unwrap();
+    assert_eq!(playlists.len(), 1);
+    assert_eq!(
+        playlists[0].entry.as_ref().unwrap().description.as_deref(),
+        Some("Songs for the road")
+    );
+    let ids: Vec<&str> = playlists[0].tracks.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, ["b", "a"]);
+}
+
+#[test]
+fn test_sqlite_keeps_details_missing_from_csv_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library.sqlite");
+
+    // A playlist reused from a CSV dump comes back without IDs for its artists and album.
+    let mut from_csv = track_record("a", "First");
+    from_csv.artists[0].id = None;
+    from_csv.album.id = None;
+    from_csv.explicit = None;
+
+    let mut library = Library::open(&path).unwrap();
+    library
+        .write_saved_tracks(&[track_record("a", "First")])
+        .unwrap();
+    library.write_saved_tracks(&[from_csv]).unwrap();
+    drop(library);
+
+    let saved_tracks = Source::Sqlite(path).read_saved_tracks().unwrap();
+    assert_eq!(saved_tracks[0].artists[0].id.as_deref(), Some("artist"));
+    assert_eq!(saved_tracks[0].album.id.as_deref(), Some("album"));
+    assert_eq!(saved_tracks[0].explicit, Some(false));
+}
+
+#[test]
+fn test_sqlite_keeps_name_and_uri_missing_from_sparse_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library.sqlite");
+
+    let mut sparse = track_record("a", "");
+    sparse.uri = None;
+
+    let mut library = Library::open(&path).unwrap();
+    library
+        .write_saved_tracks(&[track_record("a", "First")])
+        .unwrap();
+    library.write_saved_tracks(&[sparse]).unwrap();
+    drop(library);
+
+    let saved_tracks = Source::Sqlite(path).read_saved_tracks().unwrap();
+    assert_eq!(saved_tracks[0].name, "First");
+    assert_eq!(saved_tracks[0].uri.as_deref(), Some("spotify:track:a"));
+}
+
+#[test]
+fn test_sqlite_upgrades_older_databases() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library.sqlite");
+
+    // A database written before the `followed` column existed.
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE playlists (
+            id TEXT PRIMARY KEY, name TEXT NOT NULL, description TEXT, public INTEGER,
+            collaborative INTEGER NOT NULL, owner_id TEXT, owner_display_name TEXT,
+            snapshot_id TEXT, followers INTEGER, images TEXT, position INTEGER NOT NULL,
+            export_id INTEGER NOT NULL
+        );",
+    )
+    .unwrap();
+    drop(conn);
+
+    let mut followed = playlist_dump(vec![track_record("a", "First")]);
+    followed.entry.as_mut().unwrap().followed = true;
+    let mut library = Library::open(&path).unwrap();
+    library.write_playlists(&[followed]).unwrap();
+    drop(library);
+
+    let playlists = Source::Sqlite(path).read_playlists().unwrap();
+    assert!(playlists[0].entry.as_ref().unwrap().followed);
+}
+
+#[test]
+fn test_sqlite_keeps_who_added_playlist_items() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library.sqlite");
+
+    let mut first = track_record("a", "First");
+    first.added_by = Some("alice".to_string());
+    first.position = Some(0);
+    let mut local = track_record("", "Home Recording");
+    local.uri = Some("spotify:local:Me:Demos:Home+Recording:120".to_string());
+    local.position = Some(1);
+    local.is_local = true;
+    let mut second = track_record("b", "Second");
+    second.added_by = Some("bob".to_string());
+    second.position = Some(2);
+
+    let mut library = Library::open(&path).unwrap();
+    library.write_playlists(&[playlist_dump(vec![first, local, second])]).unwrap();
+    drop(library);
+
+    let playlists = Source::Sqlite(path).read_playlists().unwrap();
+    let tracks = &playlists[0].tracks;
+    // Local files cannot be stored without a track ID.
+    assert_eq!(tracks.len(), 2);
+    assert_eq!(tracks[0].added_by.as_deref(), Some("alice"));
+    assert_eq!(tracks[1].added_by.as_deref(), Some("bob"));
+    assert_eq!(tracks[1].position, Some(2));
+}
+
+#[test]
+fn test_sqlite_keeps_track_availability() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library.sqlite");
+
+    let mut restricted = track_record("a", "First");
+    restricted.is_playable = Some(false);
+    restricted.restriction = Some("market".to_string());
+    let mut playable = track_record("b", "Second");
+    playable.is_playable = Some(true);
+
+    let mut library = Library::open(&path).unwrap();
+    library.write_saved_tracks(&[restricted.clone(), playable.clone()]).unwrap();
+    library.write_playlists(&[playlist_dump(vec![playable, restricted])]).unwrap();
+    drop(library);
+
+    let source = Source::Sqlite(path);
+    let saved_tracks = source.read_saved_tracks().unwrap();
+    assert_eq!(saved_tracks[0].is_playable, Some(false));
+    assert_eq!(saved_tracks[0].restriction.as_deref(), Some("market"));
+    assert_eq!(saved_tracks[1].is_playable, Some(true));
+    assert_eq!(saved_tracks[1].restriction, None);
+
+    let playlists = source.read_playlists().unwrap();
+    assert_eq!(playlists[0].tracks[1].is_playable, Some(false));
+    assert_eq!(playlists[0].tracks[1].restriction.as_deref(), Some("market"));
+}